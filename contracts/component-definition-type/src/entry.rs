@@ -5,98 +5,132 @@ use core::result::Result;
 // https://docs.rs/ckb-std/
 use ckb_std::{
     ckb_constants::Source,
-    ckb_types::prelude::*,
+    ckb_types::{core::ScriptHashType, prelude::*},
     debug,
     error::SysError,
     high_level::{
-        load_cell_capacity, load_cell_data, load_cell_type, load_input, load_script, QueryIter,
+        load_cell_data, load_cell_lock_hash, look_for_dep_with_data_hash, look_for_dep_with_hash2,
+        QueryIter,
     },
 };
 
-use blake2b_rs::{Blake2b, Blake2bBuilder};
-use ckb_ecs_schemas::ComponentDefinitionReader;
+use ckb_ecs_schemas::{
+    type_id::verify_type_id, typed_hash, ComponentDefinitionReader, DappInfoReader,
+};
 
 use crate::error::Error;
 
 pub fn main() -> Result<(), Error> {
     verify_type_id()?;
-    verify_component_definition()
+    verify_component_definition()?;
+    verify_update()
 }
 
-// https://github.com/nervosnetwork/ckb/blob/develop/script/src/type_id.rs
-pub fn verify_type_id() -> Result<(), Error> {
-    let script = load_script()?;
-
-    // TYPE_ID script should only accept one argument,
-    // which is the hash of all inputs when creating
-    // the cell.
-    if script.args().len() != 32 {
-        return Err(Error::InvalidArgs);
+pub fn verify_component_definition() -> Result<(), Error> {
+    // There's at most one output
+    let data = match load_cell_data(0, Source::GroupOutput) {
+        Ok(data) => data,
+        Err(SysError::IndexOutOfBound) => return Ok(()),
+        Err(err) => return Err(err.into()),
+    };
+    // The unchanged definition has been verified, and the DappInfo may have been updated since.
+    match load_cell_data(0, Source::GroupInput) {
+        Ok(input_data) if input_data == data => return Ok(()),
+        Ok(_) | Err(SysError::IndexOutOfBound) => {}
+        Err(err) => return Err(err.into()),
     }
+    let definition = match ComponentDefinitionReader::from_slice(data.as_slice()) {
+        Ok(definition) => definition,
+        Err(err) => {
+            debug!("ComponentDefinition verification error: {}", err);
+            return Err(Error::InvalidData);
+        }
+    };
 
-    if cell_exists(1, Source::GroupInput)? || cell_exists(1, Source::GroupOutput)? {
-        return Err(Error::TooManyCells);
-    }
+    verify_component_declared(&definition)
+}
 
-    // If there's only one output cell with current
-    // TYPE_ID script, we are creating such a cell,
-    // we also need to validate that the first argument matches
-    // the hash of following items concatenated:
-    // 1. First CellInput of the transaction.
-    // 2. Index of the first output cell in current script group.
-    if !(cell_exists(0, Source::GroupInput)?) {
-        let first_cell_input = load_input(0, Source::Input).expect("Tx has at least one input");
-        let first_output_index = QueryIter::new(load_cell_type, Source::Output)
-            .enumerate()
-            .find(|(_, output_type_opt)| {
-                output_type_opt
-                    .as_ref()
-                    .map_or(false, |s| s.as_slice() == script.as_slice())
-            })
-            .expect("Tx must have an output in this group")
-            .0 as u64;
+// The DappInfo is referenced by the type hash of its cell, which stays the same across DappInfo
+// updates, or by its data hash.
+fn find_dapp_info(info_hash: &[u8]) -> Result<usize, Error> {
+    match look_for_dep_with_hash2(info_hash, ScriptHashType::Type) {
+        Ok(index) => return Ok(index),
+        Err(SysError::IndexOutOfBound) => {}
+        Err(err) => return Err(err.into()),
+    }
+    match look_for_dep_with_data_hash(info_hash) {
+        Ok(index) => Ok(index),
+        Err(SysError::IndexOutOfBound) => Err(Error::DappInfoNotFound),
+        Err(err) => Err(err.into()),
+    }
+}
 
-        let mut blake2b = new_blake2b();
+// The DappInfo must be provided as a cell dep and it must declare the component.
+pub fn verify_component_declared(definition: &ComponentDefinitionReader) -> Result<(), Error> {
+    let index = find_dapp_info(definition.info_hash().raw_data())?;
+    let data = load_cell_data(index, Source::CellDep)?;
+    let info = match DappInfoReader::from_slice(data.as_slice()) {
+        Ok(info) => info,
+        Err(err) => {
+            debug!("DappInfo verification error: {}", err);
+            return Err(Error::InvalidDappInfo);
+        }
+    };
 
-        blake2b.update(first_cell_input.as_slice());
-        blake2b.update(&first_output_index.to_le_bytes());
-        let mut ret = [0; 32];
-        blake2b.finalize(&mut ret);
+    let component_name = definition.component_name();
+    let components = info.components();
+    let component = components
+        .iter()
+        .find(|component| component.name().as_slice() == component_name.as_slice())
+        .ok_or(Error::ComponentNotDeclared)?;
 
-        if ret[..] != script.args().raw_data()[..] {
-            return Err(Error::InvalidTypeID);
+    if let Some(value_schema_hash) = definition.value_schema_hash() {
+        let schema_hash = typed_hash::hash(component.schema().raw_data());
+        if schema_hash[..] != value_schema_hash.raw_data()[..] {
+            return Err(Error::ValueSchemaMismatch);
         }
     }
 
     Ok(())
 }
 
-pub fn verify_component_definition() -> Result<(), Error> {
-    // There's at most one output
-    match load_cell_data(0, Source::GroupOutput) {
-        Ok(data) => match ComponentDefinitionReader::from_slice(data.as_slice()) {
-            Ok(_) => Ok(()),
-            Err(err) => {
-                debug!("ComponentDefinition verfication error: {}", err);
-                Err(Error::InvalidData)
-            }
-        },
-        Err(SysError::IndexOutOfBound) => Ok(()),
-        Err(err) => Err(err.into()),
+// Components refer to the definition by type hash, so an update must not silently change what the
+// definition means.
+pub fn verify_update() -> Result<(), Error> {
+    let input_data = match load_cell_data(0, Source::GroupInput) {
+        Ok(data) => data,
+        Err(SysError::IndexOutOfBound) => return Ok(()),
+        Err(err) => return Err(err.into()),
+    };
+    let output_data = match load_cell_data(0, Source::GroupOutput) {
+        Ok(data) => data,
+        Err(SysError::IndexOutOfBound) => return Ok(()),
+        Err(err) => return Err(err.into()),
+    };
+    if input_data == output_data {
+        return Ok(());
     }
-}
 
-pub fn cell_exists(index: usize, source: Source) -> Result<bool, Error> {
-    match load_cell_capacity(index, source) {
-        Ok(_) => Ok(true),
-        Err(SysError::IndexOutOfBound) => Ok(false),
-        Err(err) => Err(err.into()),
+    // The input has been verified when it was created.
+    let input_definition = ComponentDefinitionReader::new_unchecked(input_data.as_slice());
+    let output_definition = ComponentDefinitionReader::new_unchecked(output_data.as_slice());
+
+    if input_definition.component_name().as_slice() != output_definition.component_name().as_slice()
+        || input_definition.info_hash().as_slice() != output_definition.info_hash().as_slice()
+    {
+        return Err(Error::ImmutableFieldChanged);
+    }
+    if output_definition.version() < input_definition.version() {
+        return Err(Error::VersionDecreased);
     }
-}
 
-pub const CKB_PERSONALIZATION: &[u8] = b"ckb-default-hash";
-pub fn new_blake2b() -> Blake2b {
-    Blake2bBuilder::new(32)
-        .personal(CKB_PERSONALIZATION)
-        .build()
+    match input_definition.authority() {
+        Some(authority)
+            if QueryIter::new(load_cell_lock_hash, Source::Input)
+                .any(|hash| hash == authority.raw_data()) =>
+        {
+            Ok(())
+        }
+        _ => Err(Error::Unauthorized),
+    }
 }
@@ -1,3 +1,4 @@
+use ckb_ecs_schemas::type_id::TypeIdError;
 use ckb_std::error::SysError;
 
 include!("error_include.rs");
@@ -14,3 +15,14 @@ impl From<SysError> for Error {
         }
     }
 }
+
+impl From<TypeIdError> for Error {
+    fn from(err: TypeIdError) -> Self {
+        match err {
+            TypeIdError::Sys(err) => err.into(),
+            TypeIdError::InvalidArgs => Self::InvalidArgs,
+            TypeIdError::TooManyCells => Self::TooManyCells,
+            TypeIdError::InvalidTypeID => Self::InvalidTypeID,
+        }
+    }
+}
@@ -10,4 +10,11 @@ pub enum Error {
     TooManyCells,
     InvalidTypeID,
     InvalidData,
+    DappInfoNotFound,
+    InvalidDappInfo,
+    ComponentNotDeclared,
+    ImmutableFieldChanged,
+    Unauthorized,
+    ValueSchemaMismatch,
+    VersionDecreased,
 }
@@ -0,0 +1,144 @@
+use ckb_std::ckb_types::{packed::ScriptReader, prelude::*};
+
+use ckb_ecs_schemas::typed_hash;
+
+use crate::error::Error;
+
+/// The first byte of the compact format.
+pub const COMPACT_FORMAT: u8 = 0;
+
+const FORMAT_LEN: usize = 1;
+const LOCK_HASH_LEN: usize = 32;
+const MODE_LEN: usize = 1;
+
+/// Bits of the mode byte. Each bit appends a field to the args, in the order of the bits.
+pub mod mode {
+    /// Appends the expiry as a since value (u64 in little endian). Before the expiry, the cells
+    /// must stay in component-lock, and only after the expiry the owner can reclaim the capacity.
+    pub const EXPIRY: u8 = 0x01;
+    /// Appends the max fee in shannons (u64 in little endian) the cells may pay in a transaction
+    /// which updates a component, without the owner. Requires RESTRICT_TYPE, otherwise a
+    /// permissive type script could unlock the budget by changing the data.
+    pub const FEE_BUDGET: u8 = 0x02;
+    /// Appends the code hash (32 bytes) and the hash type (1 byte) of component-type. Every
+    /// output with the lock must carry a type script with the code hash and the hash type.
+    pub const RESTRICT_TYPE: u8 = 0x04;
+    /// Appends the component-type args V1 (33 bytes), which identifies the component definition.
+    /// Outputs match if their component-type args in any version have the same code hash and
+    /// hash type. Requires RESTRICT_TYPE.
+    pub const RESTRICT_DEFINITION: u8 = 0x08;
+    /// Appends the sponsor lock hash (32 bytes) and the sponsored capacity of each cell in
+    /// shannons (u64 in little endian). The sponsored capacity returns to the sponsor before the
+    /// owner.
+    pub const SPONSOR: u8 = 0x10;
+
+    pub const ALL: u8 = EXPIRY | FEE_BUDGET | RESTRICT_TYPE | RESTRICT_DEFINITION | SPONSOR;
+}
+
+/// The component-lock args.
+///
+/// Two formats are accepted:
+///
+/// - The legacy format is the serialized molecule table Script of the owner lock.
+/// - The compact format is the byte `COMPACT_FORMAT` followed by the owner lock hash (32 bytes), a
+///   mode byte and the fields enabled by the mode.
+///
+/// A serialized Script starts with its total size in little endian, so it starts with 0 only if
+/// it is at least 256 bytes. The compact args are at most 156 bytes, so the two formats never
+/// overlap.
+pub struct LockArgs {
+    pub owner_lock_hash: [u8; 32],
+    pub expiry: Option<u64>,
+    /// 0 if the fee budget is not set.
+    pub fee_budget: u64,
+    pub restrict_type: Option<[u8; 33]>,
+    pub restrict_definition: Option<[u8; 33]>,
+    pub sponsor: Option<Sponsor>,
+}
+
+/// Who funded the component capacity when it differs from the owner.
+pub struct Sponsor {
+    pub lock_hash: [u8; 32],
+    pub capacity: u64,
+}
+
+impl LockArgs {
+    pub fn parse(args: &[u8]) -> Result<Self, Error> {
+        if ScriptReader::from_slice(args).is_ok() {
+            return Ok(Self {
+                owner_lock_hash: typed_hash::hash(args),
+                expiry: None,
+                fee_budget: 0,
+                restrict_type: None,
+                restrict_definition: None,
+                sponsor: None,
+            });
+        }
+
+        if args.len() < FORMAT_LEN + LOCK_HASH_LEN + MODE_LEN || args[0] != COMPACT_FORMAT {
+            return Err(Error::InvalidArgs);
+        }
+        let owner_lock_hash = args[FORMAT_LEN..FORMAT_LEN + LOCK_HASH_LEN]
+            .try_into()
+            .expect("32 bytes");
+        let mode = args[FORMAT_LEN + LOCK_HASH_LEN];
+        if mode & !mode::ALL != 0
+            || (mode & (mode::FEE_BUDGET | mode::RESTRICT_DEFINITION) != 0
+                && mode & mode::RESTRICT_TYPE == 0)
+        {
+            return Err(Error::InvalidArgs);
+        }
+
+        let mut fields = &args[FORMAT_LEN + LOCK_HASH_LEN + MODE_LEN..];
+        let expiry = if mode & mode::EXPIRY != 0 {
+            Some(u64::from_le_bytes(take(&mut fields)?))
+        } else {
+            None
+        };
+        let fee_budget = if mode & mode::FEE_BUDGET != 0 {
+            u64::from_le_bytes(take(&mut fields)?)
+        } else {
+            0
+        };
+        let restrict_type = if mode & mode::RESTRICT_TYPE != 0 {
+            Some(take(&mut fields)?)
+        } else {
+            None
+        };
+        let restrict_definition = if mode & mode::RESTRICT_DEFINITION != 0 {
+            Some(take(&mut fields)?)
+        } else {
+            None
+        };
+        let sponsor = if mode & mode::SPONSOR != 0 {
+            Some(Sponsor {
+                lock_hash: take(&mut fields)?,
+                capacity: u64::from_le_bytes(take(&mut fields)?),
+            })
+        } else {
+            None
+        };
+        if !fields.is_empty() {
+            return Err(Error::InvalidArgs);
+        }
+
+        Ok(Self {
+            owner_lock_hash,
+            expiry,
+            fee_budget,
+            restrict_type,
+            restrict_definition,
+            sponsor,
+        })
+    }
+}
+
+// Splits the first N bytes from the fields.
+fn take<const N: usize>(fields: &mut &[u8]) -> Result<[u8; N], Error> {
+    if fields.len() < N {
+        return Err(Error::InvalidArgs);
+    }
+    let (field, rest) = fields.split_at(N);
+    *fields = rest;
+    Ok(field.try_into().expect("field length"))
+}
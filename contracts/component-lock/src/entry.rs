@@ -7,47 +7,237 @@ use ckb_std::{
     ckb_constants::Source,
     ckb_types::{bytes::Bytes, prelude::*},
     debug,
-    high_level::{load_cell, load_cell_capacity, load_cell_lock, load_script, QueryIter},
+    error::SysError,
+    high_level::{
+        load_cell_capacity, load_cell_data, load_cell_lock_hash, load_cell_type, load_input_since,
+        load_script, load_script_hash, QueryIter,
+    },
 };
 
-use crate::error::Error;
+use ckb_ecs_schemas::VersionedComponentArgsReader;
+
+use crate::{
+    args::{LockArgs, Sponsor},
+    error::Error,
+    since::{since_value_reached, SINCE_FLAGS_MASK},
+};
 
 pub fn main() -> Result<(), Error> {
     let script = load_script()?;
     let args: Bytes = script.args().unpack();
+    let args = LockArgs::parse(args.as_ref())?;
+    let script_hash = load_script_hash()?;
+
+    if let Some(restrict_type) = args.restrict_type {
+        verify_output_types(
+            &script_hash,
+            &restrict_type,
+            args.restrict_definition.as_ref(),
+        )?;
+    }
+
+    // The fee budget only pays for component updates, otherwise anyone could drain the capacity
+    // by submitting the cells again and again.
+    let fee_budget = if args.fee_budget > 0 && has_component_update(&script_hash)? {
+        args.fee_budget
+    } else {
+        0
+    };
+
+    // The sponsored capacity returns to the sponsor first, even when the owner is in the inputs.
+    let sponsor_due = match &args.sponsor {
+        Some(sponsor) => verify_sponsor(&script_hash, sponsor)?,
+        None => 0,
+    };
+
+    // Before the expiry, the capacity must stay in the cells with the same lock, even when the
+    // owner is in the inputs.
+    if let Some(expiry) = args.expiry {
+        if !is_expired(expiry)? {
+            return verify_capacity_preserved(&script_hash, fee_budget);
+        }
+    }
 
     // It the original owner has participating the tx, he/she can distribute the CKB capacity anywhere.
-    if QueryIter::new(load_cell_lock, Source::Input).any(|lock| lock.as_slice() == args.as_ref()) {
+    if QueryIter::new(load_cell_lock_hash, Source::Input).any(|hash| hash == args.owner_lock_hash) {
         return Ok(());
     }
 
-    // Sum capacity of inputs. Only need to scan the cells in the lock script group.
-    let inputs_capacity = QueryIter::new(load_cell_capacity, Source::GroupInput).sum::<u64>();
+    // Sum capacity of inputs. Only need to scan the cells in the lock script group. The share of
+    // the sponsor has been verified.
+    let inputs_capacity =
+        QueryIter::new(load_cell_capacity, Source::GroupInput).sum::<u64>() - sponsor_due;
 
     // Attention that output cells having the same lock script does not belong to the group.
-    let outputs_capacity = QueryIter::new(load_cell, Source::Output)
-        .filter(|cell| cell.lock().as_slice() == script.as_slice())
-        .map(|cell| cell.capacity().unpack())
-        .sum::<u64>();
+    let outputs_capacity = outputs_capacity_by_lock_hash(&script_hash)?;
 
-    // Sum of output cells has been transferred the the owner.    // Attention that output cells having the same lock script does not belong to the group.
-    let unwrapped_outputs_capacity = QueryIter::new(load_cell, Source::Output)
-        .filter(|cell| cell.lock().as_slice() == args.as_ref())
-        .map(|cell| cell.capacity().unpack())
-        .sum::<u64>();
+    // Sum of output cells has been transferred the the owner.
+    let unwrapped_outputs_capacity = outputs_capacity_by_lock_hash(&args.owner_lock_hash)?;
 
     debug!(
         "inputs: {}, outputs: {}, unwrapped_outputs: {}",
         inputs_capacity, outputs_capacity, unwrapped_outputs_capacity
     );
 
-    if outputs_capacity
-        .checked_add(unwrapped_outputs_capacity)
-        .expect("not overflow")
-        >= inputs_capacity
-    {
+    verify_accounting(
+        inputs_capacity,
+        outputs_capacity,
+        unwrapped_outputs_capacity,
+        fee_budget,
+    )
+}
+
+// Group inputs are paired with the outputs with the same lock by index. A component is updated
+// when a pair has the same type script and different data.
+fn has_component_update(script_hash: &[u8; 32]) -> Result<bool, Error> {
+    let outputs = QueryIter::new(load_cell_lock_hash, Source::Output)
+        .enumerate()
+        .filter(|(_, hash)| hash == script_hash)
+        .map(|(i, _)| i);
+    for (group_index, output_index) in outputs.enumerate() {
+        let input_type = match load_cell_type(group_index, Source::GroupInput) {
+            Ok(Some(input_type)) => input_type,
+            Ok(None) => continue,
+            Err(SysError::IndexOutOfBound) => break,
+            Err(err) => return Err(err.into()),
+        };
+        let output_type = load_cell_type(output_index, Source::Output)?;
+        if output_type.map_or(true, |output_type| {
+            output_type.as_slice() != input_type.as_slice()
+        }) {
+            continue;
+        }
+        if load_cell_data(group_index, Source::GroupInput)?
+            != load_cell_data(output_index, Source::Output)?
+        {
+            return Ok(true);
+        }
+    }
+
+    Ok(false)
+}
+
+// - Grow: the outputs with the same lock have more capacity than the inputs, someone else has
+//   sponsored the difference.
+// - Shrink: the surplus must be refunded to the owner, except the fee within the budget, which
+//   is the max fee the cells may pay.
+fn verify_accounting(inputs: u64, outputs: u64, refund: u64, fee_budget: u64) -> Result<(), Error> {
+    let surplus = match inputs.checked_sub(outputs) {
+        Some(surplus) => surplus,
+        None => return Ok(()),
+    };
+    let fee = surplus - refund.min(surplus);
+    debug!("surplus: {}, fee: {}", surplus, fee);
+
+    if fee <= fee_budget {
         Ok(())
+    } else if fee_budget > 0 {
+        Err(Error::FeeExceeded)
+    } else if refund == 0 {
+        Err(Error::ShrinkWithoutRefund)
     } else {
-        Err(Error::BalanceError)
+        Err(Error::RefundInsufficient)
+    }
+}
+
+fn outputs_capacity_by_lock_hash(lock_hash: &[u8; 32]) -> Result<u64, Error> {
+    let mut capacity = 0u64;
+    for (i, hash) in QueryIter::new(load_cell_lock_hash, Source::Output).enumerate() {
+        if &hash == lock_hash {
+            capacity = capacity
+                .checked_add(load_cell_capacity(i, Source::Output)?)
+                .expect("not overflow");
+        }
     }
+    Ok(capacity)
+}
+
+// The sponsor share of the surplus is the sponsored capacity which does not stay in the outputs
+// with the same lock. Every cell with the lock is sponsored with the capacity, because the cells
+// with the same lock args are in the same group, so the outputs keep at most the sponsored
+// capacity of each output. Returns the share, which must be refunded to the sponsor.
+fn verify_sponsor(script_hash: &[u8; 32], sponsor: &Sponsor) -> Result<u64, Error> {
+    let inputs_count = QueryIter::new(load_cell_capacity, Source::GroupInput).count() as u64;
+    let inputs_capacity = QueryIter::new(load_cell_capacity, Source::GroupInput).sum::<u64>();
+    let outputs_count = QueryIter::new(load_cell_lock_hash, Source::Output)
+        .filter(|hash| hash == script_hash)
+        .count() as u64;
+    let outputs_capacity = outputs_capacity_by_lock_hash(script_hash)?;
+
+    let sponsored_capacity = sponsor.capacity.saturating_mul(inputs_count);
+    let kept_capacity = outputs_capacity.min(sponsor.capacity.saturating_mul(outputs_count));
+    let surplus = inputs_capacity.saturating_sub(outputs_capacity);
+    let sponsor_due = surplus.min(sponsored_capacity.saturating_sub(kept_capacity));
+
+    let sponsor_refund = outputs_capacity_by_lock_hash(&sponsor.lock_hash)?;
+    debug!(
+        "sponsor due: {}, sponsor refund: {}",
+        sponsor_due, sponsor_refund
+    );
+    if sponsor_refund >= sponsor_due {
+        Ok(sponsor_due)
+    } else {
+        Err(Error::SponsorNotRefunded)
+    }
+}
+
+// The fee budget is still available before the expiry.
+fn verify_capacity_preserved(script_hash: &[u8; 32], fee_budget: u64) -> Result<(), Error> {
+    let inputs_capacity = QueryIter::new(load_cell_capacity, Source::GroupInput).sum::<u64>();
+    let outputs_capacity = outputs_capacity_by_lock_hash(script_hash)?;
+    debug!(
+        "before expiry, inputs: {}, outputs: {}",
+        inputs_capacity, outputs_capacity
+    );
+
+    if outputs_capacity.saturating_add(fee_budget) >= inputs_capacity {
+        Ok(())
+    } else {
+        Err(Error::NotExpired)
+    }
+}
+
+// The cells have expired if the since of every group input has the same flags as the expiry and
+// is not less than it. CKB ensures the since of the inputs has been reached.
+fn is_expired(expiry: u64) -> Result<bool, Error> {
+    for since in QueryIter::new(load_input_since, Source::GroupInput) {
+        if since & SINCE_FLAGS_MASK != expiry & SINCE_FLAGS_MASK
+            || !since_value_reached(since, expiry)
+        {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+const CODE_HASH_LEN: usize = 32;
+
+// Outputs with the lock can only be used by the components, so nobody can hijack the capacity
+// for other storage. The definition matches the component-type args in any version.
+fn verify_output_types(
+    script_hash: &[u8; 32],
+    restrict_type: &[u8; 33],
+    restrict_definition: Option<&[u8; 33]>,
+) -> Result<(), Error> {
+    for (i, hash) in QueryIter::new(load_cell_lock_hash, Source::Output).enumerate() {
+        if &hash != script_hash {
+            continue;
+        }
+
+        let type_script = load_cell_type(i, Source::Output)?.ok_or(Error::InvalidOutputType)?;
+        let type_matched = type_script.code_hash().as_slice() == &restrict_type[..CODE_HASH_LEN]
+            && type_script.hash_type().as_slice() == &restrict_type[CODE_HASH_LEN..];
+        let type_args = type_script.args().raw_data();
+        let definition_matched = restrict_definition.map_or(true, |definition| {
+            VersionedComponentArgsReader::from_slice(type_args.as_ref()).map_or(false, |args| {
+                args.code_hash().raw_data() == &definition[..CODE_HASH_LEN]
+                    && args.hash_type() == definition[CODE_HASH_LEN]
+            })
+        });
+        if !type_matched || !definition_matched {
+            return Err(Error::InvalidOutputType);
+        }
+    }
+
+    Ok(())
 }
@@ -6,5 +6,11 @@ pub enum Error {
     LengthNotEnough,
     Encoding,
     // Add customized errors here...
-    BalanceError,
+    ShrinkWithoutRefund,
+    InvalidArgs,
+    NotExpired,
+    FeeExceeded,
+    InvalidOutputType,
+    SponsorNotRefunded,
+    RefundInsufficient,
 }
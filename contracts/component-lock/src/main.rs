@@ -8,8 +8,10 @@
 #![cfg_attr(not(test), no_main)]
 
 // define modules
+mod args;
 mod entry;
 mod error;
+mod since;
 
 #[cfg(test)]
 extern crate alloc;
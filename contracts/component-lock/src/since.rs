@@ -0,0 +1,74 @@
+//! Comparison of since values, see
+//! https://github.com/nervosnetwork/rfcs/blob/master/rfcs/0017-tx-valid-since/0017-tx-valid-since.md
+
+pub const SINCE_FLAGS_MASK: u64 = 0xff00_0000_0000_0000;
+const SINCE_VALUE_MASK: u64 = 0x00ff_ffff_ffff_ffff;
+const SINCE_METRIC_MASK: u64 = 0x6000_0000_0000_0000;
+const SINCE_METRIC_EPOCH: u64 = 0x2000_0000_0000_0000;
+
+/// Returns whether the value of `since` is not less than the value of `expiry`. The caller must
+/// ensure that both have the same flags.
+pub fn since_value_reached(since: u64, expiry: u64) -> bool {
+    let since_value = since & SINCE_VALUE_MASK;
+    let expiry_value = expiry & SINCE_VALUE_MASK;
+    if expiry & SINCE_METRIC_MASK != SINCE_METRIC_EPOCH {
+        return since_value >= expiry_value;
+    }
+
+    let (since_number, since_index, since_length) = epoch(since_value);
+    let (expiry_number, expiry_index, expiry_length) = epoch(expiry_value);
+    if since_number != expiry_number {
+        return since_number > expiry_number;
+    }
+    since_index * expiry_length >= expiry_index * since_length
+}
+
+// Epoch with fraction: number (24 bits), index (16 bits), length (16 bits). CKB treats the
+// fraction with length 0 as 0/1.
+fn epoch(value: u64) -> (u64, u64, u64) {
+    let number = value & 0xff_ffff;
+    let index = (value >> 24) & 0xffff;
+    let length = (value >> 40) & 0xffff;
+    if length == 0 {
+        (number, 0, 1)
+    } else {
+        (number, index, length)
+    }
+}
+
+#[cfg(test)]
+fn epoch_since(number: u64, index: u64, length: u64) -> u64 {
+    SINCE_METRIC_EPOCH | (length << 40) | (index << 24) | number
+}
+
+#[test]
+fn block_number_reached() {
+    assert!(since_value_reached(100, 100));
+    assert!(since_value_reached(101, 100));
+    assert!(!since_value_reached(99, 100));
+}
+
+#[test]
+fn epoch_reached() {
+    let expiry = epoch_since(10, 1, 2);
+    assert!(since_value_reached(epoch_since(11, 0, 2), expiry));
+    assert!(!since_value_reached(epoch_since(9, 1, 2), expiry));
+    assert!(since_value_reached(epoch_since(10, 2, 4), expiry));
+    assert!(!since_value_reached(epoch_since(10, 1, 4), expiry));
+}
+
+#[test]
+fn epoch_with_zero_length_fraction() {
+    // 10 0/0 is 10 0/1
+    let expiry = epoch_since(10, 1, 2);
+    assert!(!since_value_reached(epoch_since(10, 0, 0), expiry));
+    assert!(since_value_reached(
+        epoch_since(10, 0, 0),
+        epoch_since(10, 0, 1)
+    ));
+
+    // 10 5/0 is 10 0/1
+    let expiry = epoch_since(10, 5, 0);
+    assert!(since_value_reached(epoch_since(10, 0, 1), expiry));
+    assert!(!since_value_reached(epoch_since(9, 5, 0), expiry));
+}
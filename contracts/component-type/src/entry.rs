@@ -8,66 +8,449 @@ use ckb_std::{
     ckb_types::{bytes::Bytes, core::ScriptHashType, prelude::*},
     debug,
     error::SysError,
-    high_level::{exec_cell, load_cell_data, load_script, look_for_dep_with_hash2},
+    high_level::{
+        exec_cell, load_cell_capacity, load_cell_data, load_cell_type, load_cell_type_hash,
+        load_script, load_script_hash, load_witness_args, look_for_dep_with_data_hash,
+        look_for_dep_with_hash2, spawn_cell, QueryIter,
+    },
 };
 
 use crate::error::Error;
 
-use base64::{engine::general_purpose as base64_engines, Engine as _};
-use ckb_ecs_schemas::ComponentDefinition;
+use alloc::vec::Vec;
+use ckb_ecs_delegate::{operation, DelegateContext};
+use ckb_ecs_schemas::{
+    component_flags, definition_flags,
+    layout::{verify_value, LayoutError},
+    type_id::cell_exists,
+    typed_hash::new_blake2b,
+    ComponentDataReader, ComponentDefinitionReader, EntityTypeReader, SystemDefinitionReader,
+    TypedMessageReader, ValueSchemaReader, VersionedComponentArgsReader,
+};
 
-const ARGS_LEN: usize = 33;
-const CODE_HASH_LEN: usize = 32;
-const SCRIPT_HASH_TYPE_POS: usize = 32;
+// Memory limit for the spawned delegate, in 512K pages.
+const SPAWN_MEMORY_LIMIT: u64 = 8;
 
-// Only need to differentiate type and data, no mather which vm version for data.
-fn type_or_data(byte: u8) -> ScriptHashType {
-    if byte == ScriptHashType::Type as u8 {
-        ScriptHashType::Type
-    } else {
-        ScriptHashType::Data
+// Returns None for unknown hash types.
+fn parse_hash_type(byte: u8) -> Option<ScriptHashType> {
+    match byte {
+        0 => Some(ScriptHashType::Data),
+        1 => Some(ScriptHashType::Type),
+        2 => Some(ScriptHashType::Data1),
+        4 => Some(ScriptHashType::Data2),
+        _ => None,
     }
 }
 
 pub fn main() -> Result<(), Error> {
     let script = load_script()?;
     let args: Bytes = script.args().unpack();
+    let args =
+        VersionedComponentArgsReader::from_slice(args.as_ref()).map_err(|_| Error::InvalidArgs)?;
+    let definition_id = args.code_hash().raw_data();
+    let hash_type = parse_hash_type(args.hash_type()).ok_or(Error::InvalidArgs)?;
+
+    let index = match look_for_dep_with_hash2(definition_id, hash_type) {
+        Ok(index) => index,
+        Err(SysError::IndexOutOfBound) => return Err(Error::ComponentDefinitionNotFound),
+        Err(err) => return Err(err.into()),
+    };
+    let data = load_cell_data(index, Source::CellDep)?;
+    let definition = ComponentDefinitionReader::from_slice(data.as_slice())
+        .map_err(|_| Error::InvalidComponentDefinition)?;
+
+    verify_systems(&definition, definition_id)?;
+    verify_args_flags(&args)?;
+    verify_definition_flags(&definition, &args)?;
+    // Validate the layout before running the delegates, because exec never returns. The spawned
+    // delegates return, so component-type continues with the validations after them.
+    let spawn = definition.flags() & definition_flags::SPAWN != 0;
+    if !spawn {
+        verify_layout(&definition, &args)?;
+    }
+    let message = load_message(&definition)?;
+    let operation = load_operation()?;
+    run_delegates(index, &definition, message, operation)?;
+    if spawn {
+        verify_layout(&definition, &args)?;
+    }
+
+    Ok(())
+}
+
+// Validates the entities and the values in the component data.
+fn verify_layout(
+    definition: &ComponentDefinitionReader,
+    args: &VersionedComponentArgsReader,
+) -> Result<(), Error> {
+    let args_entity = if args.flags() & component_flags::TYPE_ID == 0 {
+        args.entity().map(|entity| entity.raw_data())
+    } else {
+        None
+    };
+    let has_entity = definition.flags() & definition_flags::ENTITY != 0 || args.entity().is_some();
+    verify_entities(has_entity, args_entity, definition.entity_type())?;
+    verify_values(definition, has_entity)
+}
+
+// Classifies the script group by the lifecycle phase, see `ckb_ecs_delegate::operation`.
+fn load_operation() -> Result<u8, Error> {
+    if !cell_exists(0, Source::GroupInput)? {
+        return Ok(operation::CREATE);
+    }
+    if !cell_exists(0, Source::GroupOutput)? {
+        return Ok(operation::DESTROY);
+    }
+
+    let inputs = QueryIter::new(load_cell_data, Source::GroupInput);
+    let mut outputs = QueryIter::new(load_cell_data, Source::GroupOutput);
+    for input_data in inputs {
+        if outputs.next() != Some(input_data) {
+            return Ok(operation::UPDATE);
+        }
+    }
+    if outputs.next().is_some() {
+        return Ok(operation::UPDATE);
+    }
+
+    Ok(operation::TRANSFER)
+}
+
+// The message is in the witness of the first cell in the script group, see `MessageField`.
+fn load_message(definition: &ComponentDefinitionReader) -> Result<Vec<u8>, Error> {
+    let field = match load_cell_capacity(0, Source::GroupInput) {
+        Ok(_) => load_witness_args(0, Source::GroupInput).map(|witness| witness.input_type()),
+        Err(SysError::IndexOutOfBound) => {
+            load_witness_args(0, Source::GroupOutput).map(|witness| witness.output_type())
+        }
+        Err(err) => return Err(err.into()),
+    };
+    let message = match field {
+        Ok(field) => field.to_opt().map(|bytes| bytes.raw_data()),
+        Err(SysError::IndexOutOfBound) => None,
+        Err(err) => return Err(err.into()),
+    };
+
+    let message = match message {
+        Some(message) => message,
+        None if definition.flags() & definition_flags::MESSAGE_REQUIRED != 0 => {
+            return Err(Error::MessageRequired);
+        }
+        None => return Ok(Vec::new()),
+    };
+    let reader =
+        TypedMessageReader::from_slice(message.as_ref()).map_err(|_| Error::InvalidMessage)?;
+    if reader.target().raw_data() != load_script_hash()? {
+        return Err(Error::MessageTargetMismatch);
+    }
+
+    Ok(message.to_vec())
+}
+
+// If the definition restricts the systems, one of them must be in inputs and declare that it writes
+// the definition. Unlocking the system cell is the authorization, so the systems in cell deps,
+// which anyone can reference, do not count.
+fn verify_systems(
+    definition: &ComponentDefinitionReader,
+    definition_id: &[u8],
+) -> Result<(), Error> {
+    let systems = match definition.systems() {
+        Some(systems) if !systems.is_empty() => systems,
+        _ => return Ok(()),
+    };
+
+    for (i, type_hash) in QueryIter::new(load_cell_type_hash, Source::Input).enumerate() {
+        let is_system = type_hash.map_or(false, |type_hash| {
+            systems.iter().any(|system| system.raw_data() == type_hash)
+        });
+        if !is_system {
+            continue;
+        }
+
+        let data = load_cell_data(i, Source::Input)?;
+        let system = SystemDefinitionReader::from_slice(data.as_slice())
+            .map_err(|_| Error::InvalidSystem)?;
+        let writes = system.writes();
+        if writes.iter().any(|id| id.raw_data() == definition_id) {
+            return verify_reads(&system);
+        }
+    }
+
+    Err(Error::SystemRequired)
+}
+
+// The components in cell deps are what the system reads, and their definitions must be declared
+// in the reads or the writes of the system.
+fn verify_reads(system: &SystemDefinitionReader) -> Result<(), Error> {
+    let script = load_script()?;
+    let reads = system.reads();
+    let writes = system.writes();
+
+    for component_type in QueryIter::new(load_cell_type, Source::CellDep).flatten() {
+        if component_type.code_hash().as_slice() != script.code_hash().as_slice()
+            || component_type.hash_type().as_slice() != script.hash_type().as_slice()
+        {
+            continue;
+        }
+        let args: Bytes = component_type.args().unpack();
+        let args = match VersionedComponentArgsReader::from_slice(args.as_ref()) {
+            Ok(args) => args,
+            Err(_) => continue,
+        };
+        let definition_id = args.code_hash().raw_data();
+        let declared = reads
+            .iter()
+            .chain(writes.iter())
+            .any(|id| id.raw_data() == definition_id);
+        if !declared {
+            return Err(Error::UndeclaredRead);
+        }
+    }
+
+    Ok(())
+}
+
+fn verify_args_flags(args: &VersionedComponentArgsReader) -> Result<(), Error> {
+    let flags = args.flags();
+    if flags & (component_flags::SINGLETON | component_flags::TYPE_ID) != 0
+        && (cell_exists(1, Source::GroupInput)? || cell_exists(1, Source::GroupOutput)?)
+    {
+        return Err(Error::TooManyCells);
+    }
+    if flags & component_flags::IMMUTABLE != 0 {
+        verify_immutable()?;
+    }
+    if flags & component_flags::TYPE_ID != 0 {
+        verify_type_id(args)?;
+    }
 
-    if args.len() < ARGS_LEN {
-        return Err(Error::InvalidArgs);
+    Ok(())
+}
+
+// The type id is the hash of the definition id and the entity id, so an entity has only one
+// possible args for the definition. The component can only be created along with the entity cell,
+// which is a type id cell and cannot be created again, so there's at most one live component per
+// (definition, entity).
+fn verify_type_id(args: &VersionedComponentArgsReader) -> Result<(), Error> {
+    let type_id = args.entity().ok_or(Error::InvalidArgs)?;
+    if cell_exists(0, Source::GroupInput)? {
+        return Ok(());
+    }
+
+    let data = load_cell_data(0, Source::GroupOutput)?;
+    let entity = component_entity(&data)?;
+    let mut blake2b = new_blake2b();
+    blake2b.update(args.code_hash().raw_data());
+    blake2b.update(&entity);
+    let mut ret = [0; 32];
+    blake2b.finalize(&mut ret);
+
+    if ret[..] != type_id.raw_data()[..] {
+        return Err(Error::InvalidTypeID);
+    }
+    if !entity_created(&entity) {
+        return Err(Error::EntityNotCreated);
+    }
+
+    Ok(())
+}
+
+// The entity cell is created in the transaction if it's in outputs but not in inputs.
+fn entity_created(entity: &[u8; 32]) -> bool {
+    let is_entity = |hash: Option<[u8; 32]>| hash.as_ref() == Some(entity);
+    QueryIter::new(load_cell_type_hash, Source::Output).any(is_entity)
+        && !QueryIter::new(load_cell_type_hash, Source::Input).any(is_entity)
+}
+
+fn verify_definition_flags(
+    definition: &ComponentDefinitionReader,
+    args: &VersionedComponentArgsReader,
+) -> Result<(), Error> {
+    let flags = definition.flags();
+    // Destroying is still allowed, so the components created before the flag is set can go away.
+    if flags & definition_flags::TYPE_ID != 0
+        && args.flags() & component_flags::TYPE_ID == 0
+        && cell_exists(0, Source::GroupOutput)?
+    {
+        return Err(Error::TypeIDRequired);
+    }
+    if flags & definition_flags::IMMUTABLE != 0 {
+        verify_immutable()?;
+    }
+    if flags & definition_flags::NO_DESTROY != 0 {
+        let inputs_len = QueryIter::new(load_cell_capacity, Source::GroupInput).count();
+        if inputs_len > 0 && !cell_exists(inputs_len - 1, Source::GroupOutput)? {
+            return Err(Error::ComponentIndestructible);
+        }
+    }
+
+    Ok(())
+}
+
+// Group inputs and outputs are paired by index, and paired outputs must keep the data.
+fn verify_immutable() -> Result<(), Error> {
+    for (i, data) in QueryIter::new(load_cell_data, Source::GroupOutput).enumerate() {
+        match load_cell_data(i, Source::GroupInput) {
+            Ok(input_data) if input_data != data => return Err(Error::ComponentImmutable),
+            Ok(_) | Err(SysError::IndexOutOfBound) => {}
+            Err(err) => return Err(err.into()),
+        }
     }
 
-    match look_for_dep_with_hash2(
-        &args[0..CODE_HASH_LEN],
-        type_or_data(args[SCRIPT_HASH_TYPE_POS]),
-    ) {
-        Ok(index) => exec_dep_cell(index),
-        Err(SysError::IndexOutOfBound) => Err(Error::ComponentDefinitionNotFound),
-        Err(err) => Err(err.into()),
+    Ok(())
+}
+
+fn component_data(data: &[u8]) -> Result<ComponentDataReader, Error> {
+    ComponentDataReader::from_slice(data).map_err(|_| Error::InvalidComponentData)
+}
+
+fn component_entity(data: &[u8]) -> Result<[u8; 32], Error> {
+    let entity = component_data(data)?.entity();
+    Ok(entity.raw_data().try_into().expect("EntityId is 32 bytes"))
+}
+
+// The entity is the type hash of a cell guarded by entity-type, whose code_hash and hash_type are
+// specified in the definition.
+fn entity_exists(entity: &[u8; 32], entity_type: EntityTypeReader) -> Result<bool, Error> {
+    for source in [Source::CellDep, Source::Input, Source::Output] {
+        for (i, hash) in QueryIter::new(load_cell_type_hash, source).enumerate() {
+            if hash.as_ref() != Some(entity) {
+                continue;
+            }
+            let script = load_cell_type(i, source)?.expect("cell has the type script");
+            if script.code_hash().as_slice() == entity_type.code_hash().as_slice()
+                && script.hash_type().as_slice() == entity_type.hash_type().as_slice()
+            {
+                return Ok(true);
+            }
+        }
     }
+
+    Ok(false)
 }
 
-fn exec_dep_cell(index: usize) -> Result<(), Error> {
-    let definition = match load_cell_data(index, Source::CellDep) {
-        Ok(data) => ComponentDefinition::from_slice(data.as_ref())
-            .map_err(|_| Error::InvalidComponentDefinition),
-        Err(SysError::IndexOutOfBound) => Err(Error::ComponentDefinitionNotFound),
-        Err(err) => Err(err.into()),
-    }?;
+// Group inputs and outputs are paired by index. Paired outputs must keep the entity, and new
+// outputs must attach to an existing entity of the definition entity type. Each entity has at
+// most one output in the group.
+// If the args has the entity, all the outputs must attach to it.
+fn verify_entities(
+    has_entity: bool,
+    args_entity: Option<&[u8]>,
+    entity_type: Option<EntityTypeReader>,
+) -> Result<(), Error> {
+    if !has_entity {
+        return Ok(());
+    }
+
+    let mut entities = Vec::new();
+    for (i, data) in QueryIter::new(load_cell_data, Source::GroupOutput).enumerate() {
+        let entity = component_entity(&data)?;
+        if args_entity.map_or(false, |args_entity| args_entity != &entity[..]) {
+            return Err(Error::EntityMismatch);
+        }
+        if entities.contains(&entity) {
+            return Err(Error::DuplicatedEntity);
+        }
+
+        match load_cell_data(i, Source::GroupInput) {
+            Ok(input_data) => {
+                if component_entity(&input_data)? != entity {
+                    return Err(Error::EntityChanged);
+                }
+            }
+            Err(SysError::IndexOutOfBound) => {
+                let entity_type = entity_type.ok_or(Error::EntityTypeRequired)?;
+                if !entity_exists(&entity, entity_type)? {
+                    return Err(Error::EntityNotFound);
+                }
+            }
+            Err(err) => return Err(err.into()),
+        }
+
+        entities.push(entity);
+    }
+
+    Ok(())
+}
 
-    use ckb_ecs_schemas::ComponentDefinitionUnion::*;
-    let delegate = match definition.to_enum() {
-        ComponentDefinitionV1(v1) => v1.delegate(),
+fn verify_values(definition: &ComponentDefinitionReader, has_entity: bool) -> Result<(), Error> {
+    let value_schema_hash = match definition.value_schema_hash() {
+        Some(hash) => hash,
+        None => return Ok(()),
+    };
+    let index = match look_for_dep_with_data_hash(value_schema_hash.raw_data()) {
+        Ok(index) => index,
+        Err(SysError::IndexOutOfBound) => return Err(Error::ValueSchemaNotFound),
+        Err(err) => return Err(err.into()),
     };
-    let mut args = base64_engines::STANDARD_NO_PAD
-        .encode(&delegate.args().raw_data()[..])
-        .into_bytes();
-    args.push(0);
-    debug!("exec delegate");
-    exec_cell(
-        delegate.code_hash().as_slice(),
-        type_or_data(delegate.hash_type().into()),
-        &[core::ffi::CStr::from_bytes_with_nul(&args).expect("base64 to cstr")],
-    )?;
+    let schema_data = load_cell_data(index, Source::CellDep)?;
+    let schema = ValueSchemaReader::from_slice(schema_data.as_slice())
+        .map_err(|_| Error::InvalidValueSchema)?;
+
+    for data in QueryIter::new(load_cell_data, Source::GroupOutput) {
+        let value = if has_entity {
+            component_data(&data)?.value().raw_data()
+        } else {
+            data.as_slice()
+        };
+        verify_value(schema, value).map_err(|err| {
+            debug!("invalid component value: {:?}", err);
+            match err {
+                LayoutError::InvalidData => Error::InvalidValue,
+                LayoutError::InvalidSchema | LayoutError::TooDeep => Error::InvalidValueSchema,
+            }
+        })?;
+    }
+
+    Ok(())
+}
+
+fn run_delegates(
+    index: usize,
+    definition: &ComponentDefinitionReader,
+    message: Vec<u8>,
+    operation: u8,
+) -> Result<(), Error> {
+    // All the delegates except the last one are spawned. The last one is exec'ed unless
+    // SPAWN is set, so a definition with multiple delegates requires VM version 2.
+    let delegates_len = definition.delegates_len();
+    let spawn_last = definition.flags() & definition_flags::SPAWN != 0;
+    let component_type_hash = load_script_hash()?;
+    for i in 0..delegates_len {
+        let delegate = definition.delegate(i).expect("delegate exists");
+        let context = DelegateContext {
+            delegate_args: delegate.args().raw_data().to_vec(),
+            definition_index: index as u32,
+            component_type_hash,
+            component_name: definition.component_name().raw_data().to_vec(),
+            message: message.clone(),
+            operation,
+        };
+        let argv = context.to_argv();
+        let argv = argv.iter().map(|arg| arg.as_c_str()).collect::<Vec<_>>();
+        let code_hash = delegate.code_hash();
+        let hash_type = parse_hash_type(delegate.hash_type().as_slice()[0])
+            .ok_or(Error::InvalidComponentDefinition)?;
+
+        if i + 1 == delegates_len && !spawn_last {
+            debug!("exec delegate {}", i);
+            exec_cell(code_hash.as_slice(), hash_type, &argv)?;
+            return Ok(());
+        }
+
+        debug!("spawn delegate {}", i);
+        let exit_code = spawn_cell(
+            code_hash.as_slice(),
+            hash_type,
+            &argv,
+            SPAWN_MEMORY_LIMIT,
+            &mut Vec::new(),
+        )?;
+        if exit_code != 0 {
+            debug!("delegate {} failed with exit code {}", i, exit_code);
+            return Err(Error::DelegateFailed);
+        }
+    }
+
     Ok(())
 }
@@ -9,4 +9,26 @@ pub enum Error {
     InvalidArgs,
     ComponentDefinitionNotFound,
     InvalidComponentDefinition,
+    DelegateFailed,
+    ValueSchemaNotFound,
+    InvalidValueSchema,
+    InvalidValue,
+    InvalidComponentData,
+    EntityChanged,
+    EntityNotFound,
+    DuplicatedEntity,
+    InvalidSystem,
+    SystemRequired,
+    MessageRequired,
+    InvalidMessage,
+    MessageTargetMismatch,
+    EntityMismatch,
+    TooManyCells,
+    ComponentImmutable,
+    ComponentIndestructible,
+    InvalidTypeID,
+    EntityTypeRequired,
+    UndeclaredRead,
+    EntityNotCreated,
+    TypeIDRequired,
 }
@@ -0,0 +1,80 @@
+// Import from `core` instead of from `std` since we are in no-std mode
+use core::result::Result;
+
+// Import CKB syscalls and structures
+// https://docs.rs/ckb-std/
+use ckb_std::{
+    ckb_constants::Source,
+    ckb_types::prelude::*,
+    debug,
+    error::SysError,
+    high_level::{load_cell_data, load_cell_lock_hash, QueryIter},
+};
+
+use ckb_ecs_schemas::{type_id::verify_type_id, DappInfoReader};
+
+use crate::error::Error;
+
+pub fn main() -> Result<(), Error> {
+    verify_type_id()?;
+    verify_dapp_info()?;
+    verify_update()
+}
+
+pub fn verify_dapp_info() -> Result<(), Error> {
+    // There's at most one output
+    let data = match load_cell_data(0, Source::GroupOutput) {
+        Ok(data) => data,
+        Err(SysError::IndexOutOfBound) => return Ok(()),
+        Err(err) => return Err(err.into()),
+    };
+    let info = DappInfoReader::from_slice(data.as_slice()).map_err(|err| {
+        debug!("DappInfo verification error: {}", err);
+        Error::InvalidData
+    })?;
+
+    // Component names are the keys to lookup the schemas, they must be unique.
+    let components = info.components();
+    for i in 0..components.len() {
+        let name = components.get_unchecked(i).name();
+        for j in (i + 1)..components.len() {
+            if components.get_unchecked(j).name().as_slice() == name.as_slice() {
+                return Err(Error::DuplicatedComponent);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// Only the owner can update or destroy the DappInfo cell.
+pub fn verify_update() -> Result<(), Error> {
+    let input_data = match load_cell_data(0, Source::GroupInput) {
+        Ok(data) => data,
+        Err(SysError::IndexOutOfBound) => return Ok(()),
+        Err(err) => return Err(err.into()),
+    };
+    // The input has been verified when it was created.
+    let input_info = DappInfoReader::new_unchecked(input_data.as_slice());
+
+    let owner = input_info.owner();
+    if !QueryIter::new(load_cell_lock_hash, Source::Input).any(|hash| hash == owner.raw_data()) {
+        return Err(Error::Unauthorized);
+    }
+
+    let output_data = match load_cell_data(0, Source::GroupOutput) {
+        Ok(data) => data,
+        Err(SysError::IndexOutOfBound) => return Ok(()),
+        Err(err) => return Err(err.into()),
+    };
+    let output_info = DappInfoReader::new_unchecked(output_data.as_slice());
+
+    if output_info.name().as_slice() != input_info.name().as_slice() {
+        return Err(Error::NameChanged);
+    }
+    if u32::from(output_info.version()) <= u32::from(input_info.version()) {
+        return Err(Error::VersionNotIncreased);
+    }
+
+    Ok(())
+}
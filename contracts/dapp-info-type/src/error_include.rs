@@ -0,0 +1,17 @@
+#[repr(i8)]
+#[cfg_attr(test, allow(dead_code))]
+pub enum Error {
+    IndexOutOfBound = 1,
+    ItemMissing,
+    LengthNotEnough,
+    Encoding,
+    // Add customized errors here...
+    InvalidArgs,
+    TooManyCells,
+    InvalidTypeID,
+    InvalidData,
+    DuplicatedComponent,
+    Unauthorized,
+    NameChanged,
+    VersionNotIncreased,
+}
@@ -0,0 +1,36 @@
+// Import from `core` instead of from `std` since we are in no-std mode
+use core::result::Result;
+
+// Import CKB syscalls and structures
+// https://docs.rs/ckb-std/
+use ckb_std::{debug, env::argv, high_level::load_script_hash};
+
+use crate::error::Error;
+
+use ckb_ecs_delegate::DelegateContext;
+
+// An example delegate which expects its args to be a mask of the allowed operations followed by
+// the component name.
+pub fn main() -> Result<(), Error> {
+    let context = DelegateContext::from_argv(argv().iter().map(|arg| &**arg)).map_err(|err| {
+        debug!("invalid delegate context: {:?}", err);
+        Error::InvalidContext
+    })?;
+
+    let (operations, component_name) = context
+        .delegate_args
+        .split_first()
+        .ok_or(Error::InvalidArgs)?;
+    if operations & context.operation == 0 {
+        return Err(Error::OperationNotAllowed);
+    }
+    if context.component_name != component_name {
+        return Err(Error::ComponentNameMismatch);
+    }
+    // exec keeps the script group, so the current script is the component-type script.
+    if context.component_type_hash != load_script_hash()? {
+        return Err(Error::ComponentTypeHashMismatch);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,12 @@
+// Import from `core` instead of from `std` since we are in no-std mode
+use core::result::Result;
+
+use ckb_ecs_schemas::type_id::verify_type_id;
+
+use crate::error::Error;
+
+// The entity id is the type hash of the entity cell, the entity cell is a plain type id cell.
+pub fn main() -> Result<(), Error> {
+    verify_type_id()?;
+    Ok(())
+}
@@ -0,0 +1,51 @@
+// Import from `core` instead of from `std` since we are in no-std mode
+use core::result::Result;
+
+// Import CKB syscalls and structures
+// https://docs.rs/ckb-std/
+use ckb_std::{
+    ckb_constants::Source, ckb_types::prelude::*, debug, error::SysError,
+    high_level::load_cell_data,
+};
+
+use ckb_ecs_schemas::{type_id::verify_type_id, Uint64Reader};
+
+use crate::error::Error;
+
+// The nonce cell is a type id cell which data is a Uint64 counter. It starts from 0 and each
+// transaction can only increase it by 1. typed-message-lock consumes a nonce per signed message.
+pub fn main() -> Result<(), Error> {
+    verify_type_id()?;
+    verify_nonce()
+}
+
+fn load_nonce(index: usize, source: Source) -> Result<Option<u64>, Error> {
+    let data = match load_cell_data(index, source) {
+        Ok(data) => data,
+        Err(SysError::IndexOutOfBound) => return Ok(None),
+        Err(err) => return Err(err.into()),
+    };
+    match Uint64Reader::from_slice(data.as_slice()) {
+        Ok(nonce) => Ok(Some(nonce.into())),
+        Err(err) => {
+            debug!("nonce verification error: {}", err);
+            Err(Error::InvalidData)
+        }
+    }
+}
+
+pub fn verify_nonce() -> Result<(), Error> {
+    // There's at most one input and one output
+    let output_nonce = match load_nonce(0, Source::GroupOutput)? {
+        Some(nonce) => nonce,
+        None => return Ok(()),
+    };
+
+    match load_nonce(0, Source::GroupInput)? {
+        Some(input_nonce) if input_nonce.checked_add(1) != Some(output_nonce) => {
+            Err(Error::InvalidIncrement)
+        }
+        None if output_nonce != 0 => Err(Error::InvalidInitialNonce),
+        _ => Ok(()),
+    }
+}
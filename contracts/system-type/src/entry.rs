@@ -0,0 +1,57 @@
+// Import from `core` instead of from `std` since we are in no-std mode
+use core::result::Result;
+
+// Import CKB syscalls and structures
+// https://docs.rs/ckb-std/
+use ckb_std::{
+    ckb_constants::Source, ckb_types::prelude::*, debug, error::SysError,
+    high_level::load_cell_data,
+};
+
+use ckb_ecs_schemas::{type_id::verify_type_id, SystemDefinitionReader};
+
+use crate::error::Error;
+
+pub fn main() -> Result<(), Error> {
+    verify_type_id()?;
+    verify_system_definition()?;
+    verify_update()
+}
+
+pub fn verify_system_definition() -> Result<(), Error> {
+    // There's at most one output
+    match load_cell_data(0, Source::GroupOutput) {
+        Ok(data) => match SystemDefinitionReader::from_slice(data.as_slice()) {
+            Ok(_) => Ok(()),
+            Err(err) => {
+                debug!("SystemDefinition verification error: {}", err);
+                Err(Error::InvalidData)
+            }
+        },
+        Err(SysError::IndexOutOfBound) => Ok(()),
+        Err(err) => Err(err.into()),
+    }
+}
+
+// The name identifies the system for humans, the declared reads and writes can change.
+pub fn verify_update() -> Result<(), Error> {
+    let input_data = match load_cell_data(0, Source::GroupInput) {
+        Ok(data) => data,
+        Err(SysError::IndexOutOfBound) => return Ok(()),
+        Err(err) => return Err(err.into()),
+    };
+    let output_data = match load_cell_data(0, Source::GroupOutput) {
+        Ok(data) => data,
+        Err(SysError::IndexOutOfBound) => return Ok(()),
+        Err(err) => return Err(err.into()),
+    };
+
+    // The input has been verified when it was created.
+    let input_system = SystemDefinitionReader::new_unchecked(input_data.as_slice());
+    let output_system = SystemDefinitionReader::new_unchecked(output_data.as_slice());
+    if output_system.name().as_slice() != input_system.name().as_slice() {
+        return Err(Error::NameChanged);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,28 @@
+use ckb_ecs_schemas::type_id::TypeIdError;
+use ckb_std::error::SysError;
+
+include!("error_include.rs");
+
+impl From<SysError> for Error {
+    fn from(err: SysError) -> Self {
+        use SysError::*;
+        match err {
+            IndexOutOfBound => Self::IndexOutOfBound,
+            ItemMissing => Self::ItemMissing,
+            LengthNotEnough(_) => Self::LengthNotEnough,
+            Encoding => Self::Encoding,
+            Unknown(err_code) => panic!("unexpected sys error {}", err_code),
+        }
+    }
+}
+
+impl From<TypeIdError> for Error {
+    fn from(err: TypeIdError) -> Self {
+        match err {
+            TypeIdError::Sys(err) => err.into(),
+            TypeIdError::InvalidArgs => Self::InvalidArgs,
+            TypeIdError::TooManyCells => Self::TooManyCells,
+            TypeIdError::InvalidTypeID => Self::InvalidTypeID,
+        }
+    }
+}
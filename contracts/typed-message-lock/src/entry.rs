@@ -0,0 +1,200 @@
+// Import from `core` instead of from `std` since we are in no-std mode
+use core::result::Result;
+
+// Import CKB syscalls and structures
+// https://docs.rs/ckb-std/
+use ckb_std::{
+    ckb_constants::Source,
+    ckb_types::{bytes::Bytes, core::ScriptHashType, packed::Script, prelude::*},
+    debug,
+    error::SysError,
+    high_level::{
+        exec_cell, load_cell, load_cell_capacity, load_cell_data, load_cell_lock,
+        load_cell_lock_hash, load_cell_type, load_cell_type_hash, load_script, load_witness_args,
+        look_for_dep_with_hash2, QueryIter,
+    },
+};
+
+use crate::error::Error;
+
+use alloc::vec::Vec;
+use ckb_ecs_delegate::VerifierContext;
+use ckb_ecs_schemas::{
+    definition_flags, typed_hash::domain_separator, ComponentDefinitionReader,
+    TypedMessageLockArgsReader, TypedMessageReader, Uint64Reader, VersionedComponentArgsReader,
+};
+
+// Returns None for unknown hash types, the same mapping as component-type.
+fn parse_hash_type(byte: u8) -> Option<ScriptHashType> {
+    match byte {
+        0 => Some(ScriptHashType::Data),
+        1 => Some(ScriptHashType::Type),
+        2 => Some(ScriptHashType::Data1),
+        4 => Some(ScriptHashType::Data2),
+        _ => None,
+    }
+}
+
+// Unlocks when the owner is in the inputs, or when the witness of the first group input carries
+// a TypedMessage signed by the owner. The signature is checked by the verifier script. The message
+// unlocks all the group inputs, so all of them must be components of the message target.
+pub fn main() -> Result<(), Error> {
+    let script = load_script()?;
+    let args: Bytes = script.args().unpack();
+    let lock_args =
+        TypedMessageLockArgsReader::from_slice(args.as_ref()).map_err(|_| Error::InvalidArgs)?;
+
+    let owner = lock_args.owner().raw_data();
+    if QueryIter::new(load_cell_lock_hash, Source::Input).any(|hash| hash == owner) {
+        return Ok(());
+    }
+
+    verify_capacity(script.as_slice())?;
+
+    // The signature is in the lock field and the message is in the input_type field, which is
+    // also where component-type reads the message.
+    let witness = match load_witness_args(0, Source::GroupInput) {
+        Ok(witness) => witness,
+        Err(SysError::IndexOutOfBound) => return Err(Error::SignatureRequired),
+        Err(err) => return Err(err.into()),
+    };
+    let signature = witness
+        .lock()
+        .to_opt()
+        .ok_or(Error::SignatureRequired)?
+        .raw_data();
+    let message = witness
+        .input_type()
+        .to_opt()
+        .ok_or(Error::MessageRequired)?
+        .raw_data();
+    let message =
+        TypedMessageReader::from_slice(message.as_ref()).map_err(|_| Error::InvalidMessage)?;
+
+    let domain = load_domain(lock_args.nonce().raw_data())?;
+    let target = message.target().raw_data();
+    for component_type_hash in QueryIter::new(load_cell_type_hash, Source::GroupInput) {
+        if component_type_hash.as_ref().map(|hash| &hash[..]) != Some(target) {
+            return Err(Error::MessageTargetMismatch);
+        }
+    }
+    verify_nonce(
+        &script,
+        lock_args.nonce().raw_data(),
+        message.nonce().into(),
+    )?;
+
+    let context = VerifierContext {
+        verifier_args: lock_args.verifier().args().raw_data().to_vec(),
+        digest: message.digest(&domain),
+        signature: signature.to_vec(),
+    };
+    let argv = context.to_argv();
+    let argv = argv.iter().map(|arg| arg.as_c_str()).collect::<Vec<_>>();
+
+    let verifier = lock_args.verifier();
+    let hash_type =
+        parse_hash_type(verifier.hash_type().as_slice()[0]).ok_or(Error::InvalidArgs)?;
+    debug!("exec verifier");
+    exec_cell(verifier.code_hash().raw_data(), hash_type, &argv)?;
+    Ok(())
+}
+
+// Without the owner, the capacity must stay in the cells guarded by the same lock.
+fn verify_capacity(script: &[u8]) -> Result<(), Error> {
+    let inputs_capacity = QueryIter::new(load_cell_capacity, Source::GroupInput).sum::<u64>();
+    let outputs_capacity = QueryIter::new(load_cell, Source::Output)
+        .filter(|cell| cell.lock().as_slice() == script)
+        .map(|cell| cell.capacity().unpack())
+        .sum::<u64>();
+
+    debug!("inputs: {}, outputs: {}", inputs_capacity, outputs_capacity);
+    if outputs_capacity >= inputs_capacity {
+        Ok(())
+    } else {
+        Err(Error::CapacityNotPreserved)
+    }
+}
+
+fn find_nonce(nonce_type_hash: &[u8], source: Source) -> Result<Option<u64>, Error> {
+    let index = match QueryIter::new(load_cell_type_hash, source)
+        .position(|hash| hash.as_ref().map(|hash| &hash[..]) == Some(nonce_type_hash))
+    {
+        Some(index) => index,
+        None => return Ok(None),
+    };
+    let data = load_cell_data(index, source)?;
+    let nonce = Uint64Reader::from_slice(data.as_slice()).map_err(|_| Error::InvalidNonce)?;
+    Ok(Some(nonce.into()))
+}
+
+// The message must use the current nonce and the nonce cell must be increased by 1, which
+// rejects replaying the message in later transactions. Within a transaction, the nonce can only
+// be consumed by one lock script group.
+fn verify_nonce(script: &Script, nonce_type_hash: &[u8], message_nonce: u64) -> Result<(), Error> {
+    let input_nonce = find_nonce(nonce_type_hash, Source::Input)?.ok_or(Error::NonceRequired)?;
+    if input_nonce != message_nonce {
+        return Err(Error::NonceMismatch);
+    }
+    if find_nonce(nonce_type_hash, Source::Output)? != input_nonce.checked_add(1) {
+        return Err(Error::NonceNotIncreased);
+    }
+
+    for lock in QueryIter::new(load_cell_lock, Source::Input) {
+        if lock.as_slice() == script.as_slice()
+            || lock.code_hash().as_slice() != script.code_hash().as_slice()
+            || lock.hash_type().as_slice() != script.hash_type().as_slice()
+        {
+            continue;
+        }
+        let args: Bytes = lock.args().unpack();
+        let shares_nonce = TypedMessageLockArgsReader::from_slice(args.as_ref())
+            .map_or(false, |args| args.nonce().raw_data() == nonce_type_hash);
+        if shares_nonce {
+            return Err(Error::NonceReused);
+        }
+    }
+
+    Ok(())
+}
+
+// The domain binds the message to the DappInfo and the definition of the component in the first
+// group input, and to the nonce cell, so a message cannot be replayed on a lock using another nonce
+// cell.
+//
+// The signature only binds the message, so the definition must set MESSAGE_REQUIRED. Then
+// component-type verifies the message target and passes the message to the delegates, which
+// verify that the component transition follows the message.
+fn load_domain(nonce_type_hash: &[u8]) -> Result<[u8; 32], Error> {
+    let component_type = match load_cell_type(0, Source::GroupInput)? {
+        Some(component_type) => component_type,
+        None => return Err(Error::ComponentRequired),
+    };
+    let args: Bytes = component_type.args().unpack();
+    let args = VersionedComponentArgsReader::from_slice(args.as_ref())
+        .map_err(|_| Error::InvalidComponentArgs)?;
+    let hash_type = parse_hash_type(args.hash_type()).ok_or(Error::InvalidComponentArgs)?;
+
+    let definition_hash = args.code_hash().raw_data();
+    let index = match look_for_dep_with_hash2(definition_hash, hash_type) {
+        Ok(index) => index,
+        Err(SysError::IndexOutOfBound) => return Err(Error::ComponentDefinitionNotFound),
+        Err(err) => return Err(err.into()),
+    };
+    let data = load_cell_data(index, Source::CellDep)?;
+    let definition = ComponentDefinitionReader::from_slice(data.as_slice())
+        .map_err(|_| Error::InvalidComponentDefinition)?;
+    if definition.flags() & definition_flags::MESSAGE_REQUIRED == 0 {
+        return Err(Error::MessageNotRequired);
+    }
+
+    Ok(domain_separator(
+        definition
+            .info_hash()
+            .raw_data()
+            .try_into()
+            .expect("Byte32 is 32 bytes"),
+        definition_hash.try_into().expect("code hash is 32 bytes"),
+        nonce_type_hash.try_into().expect("Byte32 is 32 bytes"),
+    ))
+}
@@ -0,0 +1,25 @@
+#[repr(i8)]
+#[cfg_attr(test, allow(dead_code))]
+pub enum Error {
+    IndexOutOfBound = 1,
+    ItemMissing,
+    LengthNotEnough,
+    Encoding,
+    // Add customized errors here...
+    InvalidArgs,
+    CapacityNotPreserved,
+    SignatureRequired,
+    MessageRequired,
+    InvalidMessage,
+    ComponentRequired,
+    ComponentDefinitionNotFound,
+    InvalidComponentDefinition,
+    MessageTargetMismatch,
+    NonceRequired,
+    InvalidNonce,
+    NonceMismatch,
+    NonceNotIncreased,
+    NonceReused,
+    InvalidComponentArgs,
+    MessageNotRequired,
+}
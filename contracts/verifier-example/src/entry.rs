@@ -0,0 +1,34 @@
+// Import from `core` instead of from `std` since we are in no-std mode
+use core::result::Result;
+
+// Import CKB syscalls and structures
+// https://docs.rs/ckb-std/
+use ckb_std::{debug, env::argv};
+
+use crate::error::Error;
+
+use ckb_ecs_delegate::VerifierContext;
+use ckb_ecs_schemas::typed_hash::new_blake2b;
+
+// An example verifier for typed-message-lock, which expects the signature to be
+// `hash(verifier_args || digest)`. It only demonstrates the protocol and is not secure, since
+// anyone who knows the args can sign. Real verifiers should check a secp256k1 or other
+// signature against the public key in the args.
+pub fn main() -> Result<(), Error> {
+    let context = VerifierContext::from_argv(argv().iter().map(|arg| &**arg)).map_err(|err| {
+        debug!("invalid verifier context: {:?}", err);
+        Error::InvalidContext
+    })?;
+
+    let mut blake2b = new_blake2b();
+    blake2b.update(&context.verifier_args);
+    blake2b.update(&context.digest);
+    let mut expected = [0u8; 32];
+    blake2b.finalize(&mut expected);
+
+    if context.signature != expected {
+        return Err(Error::InvalidSignature);
+    }
+
+    Ok(())
+}
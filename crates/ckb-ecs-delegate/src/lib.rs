@@ -0,0 +1,218 @@
+//! The argv protocol between component-type and the component delegates.
+//!
+//! component-type executes a delegate with the following arguments, each one encoded in base64
+//! (standard alphabet without padding):
+//!
+//! 0. The delegate script args.
+//! 1. The index of the ComponentDefinition cell in cell deps, as u32 in little endian.
+//! 2. The script hash of the component-type script which invokes the delegate.
+//! 3. The component name.
+//! 4. The TypedMessage in the witness, empty if there's no message.
+//! 5. The operation of the script group, a single byte defined in `operation`.
+//!
+//! typed-message-lock executes a signature verifier with the following arguments, encoded in the
+//! same way:
+//!
+//! 0. The verifier script args.
+//! 1. The digest of the TypedMessage, see `ckb_ecs_schemas::typed_hash`.
+//! 2. The signature in the witness lock field.
+#![no_std]
+extern crate alloc;
+
+use alloc::{ffi::CString, vec::Vec};
+use base64::{engine::general_purpose as base64_engines, Engine as _};
+use core::ffi::CStr;
+
+pub const ARGC: usize = 6;
+pub const VERIFIER_ARGC: usize = 3;
+
+/// The lifecycle operations of a component-type script group. Each one is a distinct bit so
+/// delegates can accept a set of operations as a mask.
+pub mod operation {
+    /// The group has outputs only.
+    pub const CREATE: u8 = 0x01;
+    /// The group has both inputs and outputs, and the data changes.
+    pub const UPDATE: u8 = 0x02;
+    /// The group has inputs only.
+    pub const DESTROY: u8 = 0x04;
+    /// The group has the same cell data in inputs and outputs, paired by index. Only the other
+    /// fields such as the lock can change.
+    pub const TRANSFER: u8 = 0x08;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    ArgsCountMismatch,
+    Encoding,
+    InvalidLength,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct DelegateContext {
+    pub delegate_args: Vec<u8>,
+    pub definition_index: u32,
+    pub component_type_hash: [u8; 32],
+    pub component_name: Vec<u8>,
+    pub message: Vec<u8>,
+    pub operation: u8,
+}
+
+impl DelegateContext {
+    pub fn to_argv(&self) -> Vec<CString> {
+        [
+            &self.delegate_args[..],
+            &self.definition_index.to_le_bytes()[..],
+            &self.component_type_hash[..],
+            &self.component_name[..],
+            &self.message[..],
+            &[self.operation][..],
+        ]
+        .iter()
+        .map(|arg| encode(arg))
+        .collect()
+    }
+
+    /// Parses the context from argv. Delegates can pass `ckb_std::env::argv()` in as
+    /// `argv().iter().map(|arg| &**arg)`.
+    pub fn from_argv<'a, I>(argv: I) -> Result<Self, Error>
+    where
+        I: IntoIterator<Item = &'a CStr>,
+    {
+        let argv = argv
+            .into_iter()
+            .map(decode)
+            .collect::<Result<Vec<_>, _>>()?;
+        if argv.len() != ARGC {
+            return Err(Error::ArgsCountMismatch);
+        }
+
+        let mut argv = argv.into_iter();
+        let delegate_args = argv.next().expect("delegate args");
+        let definition_index = u32::from_le_bytes(
+            argv.next()
+                .expect("definition index")
+                .try_into()
+                .map_err(|_| Error::InvalidLength)?,
+        );
+        let component_type_hash = argv
+            .next()
+            .expect("component type hash")
+            .try_into()
+            .map_err(|_| Error::InvalidLength)?;
+        let component_name = argv.next().expect("component name");
+        let message = argv.next().expect("message");
+        let [operation]: [u8; 1] = argv
+            .next()
+            .expect("operation")
+            .try_into()
+            .map_err(|_| Error::InvalidLength)?;
+
+        Ok(Self {
+            delegate_args,
+            definition_index,
+            component_type_hash,
+            component_name,
+            message,
+            operation,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct VerifierContext {
+    pub verifier_args: Vec<u8>,
+    pub digest: [u8; 32],
+    pub signature: Vec<u8>,
+}
+
+impl VerifierContext {
+    pub fn to_argv(&self) -> Vec<CString> {
+        [
+            &self.verifier_args[..],
+            &self.digest[..],
+            &self.signature[..],
+        ]
+        .iter()
+        .map(|arg| encode(arg))
+        .collect()
+    }
+
+    /// Parses the context from argv, see `DelegateContext::from_argv`.
+    pub fn from_argv<'a, I>(argv: I) -> Result<Self, Error>
+    where
+        I: IntoIterator<Item = &'a CStr>,
+    {
+        let argv = argv
+            .into_iter()
+            .map(decode)
+            .collect::<Result<Vec<_>, _>>()?;
+        if argv.len() != VERIFIER_ARGC {
+            return Err(Error::ArgsCountMismatch);
+        }
+
+        let mut argv = argv.into_iter();
+        let verifier_args = argv.next().expect("verifier args");
+        let digest = argv
+            .next()
+            .expect("digest")
+            .try_into()
+            .map_err(|_| Error::InvalidLength)?;
+        let signature = argv.next().expect("signature");
+
+        Ok(Self {
+            verifier_args,
+            digest,
+            signature,
+        })
+    }
+}
+
+fn encode(arg: &[u8]) -> CString {
+    CString::new(base64_engines::STANDARD_NO_PAD.encode(arg)).expect("base64 has no nul")
+}
+
+fn decode(arg: &CStr) -> Result<Vec<u8>, Error> {
+    base64_engines::STANDARD_NO_PAD
+        .decode(arg.to_bytes())
+        .map_err(|_| Error::Encoding)
+}
+
+#[test]
+fn argv_roundtrip() {
+    let context = DelegateContext {
+        delegate_args: b"args".to_vec(),
+        definition_index: 3,
+        component_type_hash: [42u8; 32],
+        component_name: b"position".to_vec(),
+        message: Vec::new(),
+        operation: operation::UPDATE,
+    };
+    let argv = context.to_argv();
+    assert_eq!(argv.len(), ARGC);
+    assert_eq!(argv[0].to_bytes(), b"YXJncw");
+
+    let parsed = DelegateContext::from_argv(argv.iter().map(|arg| arg.as_c_str()));
+    assert_eq!(parsed, Ok(context));
+    assert_eq!(
+        DelegateContext::from_argv(argv[..1].iter().map(|arg| arg.as_c_str())),
+        Err(Error::ArgsCountMismatch)
+    );
+}
+
+#[test]
+fn verifier_argv_roundtrip() {
+    let context = VerifierContext {
+        verifier_args: b"args".to_vec(),
+        digest: [7u8; 32],
+        signature: b"signature".to_vec(),
+    };
+    let argv = context.to_argv();
+    assert_eq!(argv.len(), VERIFIER_ARGC);
+
+    let parsed = VerifierContext::from_argv(argv.iter().map(|arg| arg.as_c_str()));
+    assert_eq!(parsed, Ok(context));
+    assert_eq!(
+        VerifierContext::from_argv(argv[..2].iter().map(|arg| arg.as_c_str())),
+        Err(Error::ArgsCountMismatch)
+    );
+}
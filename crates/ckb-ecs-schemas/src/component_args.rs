@@ -0,0 +1,90 @@
+use crate::schemas::{Byte32Reader, ComponentArgsReader, ComponentArgsV2Reader, EntityIdReader};
+use molecule::{error::VerificationError, prelude::*};
+
+/// Bits of ComponentArgsV2.flags.
+pub mod flags {
+    /// The component-type script group has at most one input and one output.
+    pub const SINGLETON: u8 = 0x01;
+    /// The component cannot be updated once created, but it can be destroyed.
+    pub const IMMUTABLE: u8 = 0x02;
+    /// The entity field is a type id rather than an entity. It must be the hash of the definition
+    /// id and the entity id in the cell data, and the component can only be created in the
+    /// transaction creating the entity cell. So there's at most one live component per
+    /// (definition, entity). Use a dapp-wide entity for a global resource.
+    pub const TYPE_ID: u8 = 0x04;
+}
+
+/// The component-type args in any version. ComponentArgs is 33 bytes and ComponentArgsV2 is 66
+/// bytes.
+#[derive(Clone, Copy)]
+pub enum VersionedComponentArgsReader<'r> {
+    V1(ComponentArgsReader<'r>),
+    V2(ComponentArgsV2Reader<'r>),
+}
+
+impl<'r> VersionedComponentArgsReader<'r> {
+    pub fn from_slice(slice: &'r [u8]) -> Result<Self, VerificationError> {
+        if slice.len() == ComponentArgsV2Reader::TOTAL_SIZE {
+            ComponentArgsV2Reader::from_slice(slice).map(Self::V2)
+        } else {
+            ComponentArgsReader::from_slice(slice).map(Self::V1)
+        }
+    }
+
+    /// The definition id.
+    pub fn code_hash(&self) -> Byte32Reader<'r> {
+        match self {
+            Self::V1(v1) => v1.code_hash(),
+            Self::V2(v2) => v2.code_hash(),
+        }
+    }
+
+    pub fn hash_type(&self) -> u8 {
+        match self {
+            Self::V1(v1) => v1.hash_type().as_slice()[0],
+            Self::V2(v2) => v2.hash_type().as_slice()[0],
+        }
+    }
+
+    /// Returns None for V1, which is not attached to an entity by args. It's the type id rather
+    /// than the entity if the flags set TYPE_ID.
+    pub fn entity(&self) -> Option<EntityIdReader<'r>> {
+        match self {
+            Self::V1(_) => None,
+            Self::V2(v2) => Some(v2.entity()),
+        }
+    }
+
+    pub fn flags(&self) -> u8 {
+        match self {
+            Self::V1(_) => 0,
+            Self::V2(v2) => v2.flags().as_slice()[0],
+        }
+    }
+}
+
+#[test]
+fn parse_versioned_component_args() {
+    use crate::schemas::ComponentArgsV2Builder;
+
+    let args = ComponentArgsV2Builder::default()
+        .code_hash([1u8; 32].into())
+        .hash_type(1.into())
+        .entity([2u8; 32].into())
+        .flags(flags::SINGLETON.into())
+        .build();
+    let reader = VersionedComponentArgsReader::from_slice(args.as_slice()).expect("v2");
+    assert_eq!(reader.code_hash().raw_data(), &[1u8; 32]);
+    assert_eq!(reader.hash_type(), 1);
+    assert_eq!(
+        reader.entity().map(|entity| entity.raw_data()),
+        Some(&[2u8; 32][..])
+    );
+    assert_eq!(reader.flags(), flags::SINGLETON);
+
+    let reader = VersionedComponentArgsReader::from_slice(&args.as_slice()[..33]).expect("v1");
+    assert!(reader.entity().is_none());
+    assert_eq!(reader.flags(), 0);
+
+    assert!(VersionedComponentArgsReader::from_slice(&args.as_slice()[..34]).is_err());
+}
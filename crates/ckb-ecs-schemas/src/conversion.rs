@@ -1,4 +1,6 @@
-use crate::schemas::{Byte32, Bytes, String};
+use crate::schemas::{
+    Byte32, Byte32Opt, Bytes, EntityId, String, Uint32, Uint32Reader, Uint64, Uint64Reader,
+};
 use molecule::prelude::*;
 
 impl From<[u8; 32]> for Byte32 {
@@ -7,6 +9,18 @@ impl From<[u8; 32]> for Byte32 {
     }
 }
 
+impl From<[u8; 32]> for EntityId {
+    fn from(value: [u8; 32]) -> Self {
+        EntityId::from_slice(&value).expect("EntityId from [u8; 32]")
+    }
+}
+
+impl From<Option<[u8; 32]>> for Byte32Opt {
+    fn from(value: Option<[u8; 32]>) -> Self {
+        Byte32Opt::new_builder().set(value.map(Into::into)).build()
+    }
+}
+
 impl From<&[u8]> for Bytes {
     fn from(value: &[u8]) -> Self {
         let len = (value.len() as u32).to_le_bytes();
@@ -27,3 +41,31 @@ impl From<&str> for String {
         String::new_unchecked(v.into())
     }
 }
+
+impl From<u32> for Uint32 {
+    fn from(value: u32) -> Self {
+        Uint32::new_unchecked(value.to_le_bytes().to_vec().into())
+    }
+}
+
+impl<'r> From<Uint32Reader<'r>> for u32 {
+    fn from(value: Uint32Reader<'r>) -> Self {
+        let mut buf = [0u8; 4];
+        buf.copy_from_slice(value.raw_data());
+        u32::from_le_bytes(buf)
+    }
+}
+
+impl From<u64> for Uint64 {
+    fn from(value: u64) -> Self {
+        Uint64::new_unchecked(value.to_le_bytes().to_vec().into())
+    }
+}
+
+impl<'r> From<Uint64Reader<'r>> for u64 {
+    fn from(value: Uint64Reader<'r>) -> Self {
+        let mut buf = [0u8; 8];
+        buf.copy_from_slice(value.raw_data());
+        u64::from_le_bytes(buf)
+    }
+}
@@ -0,0 +1,119 @@
+use crate::schemas::{
+    Byte32Reader, Byte32VecReader, ComponentDefinitionReader, ComponentDefinitionUnionReader::*,
+    EntityTypeReader, ScriptReader, StringReader,
+};
+use molecule::prelude::*;
+
+/// Bits of ComponentDefinitionV2.flags.
+pub mod flags {
+    /// Runs the delegates via spawn instead of exec, so component-type validates the entities and
+    /// the values after the delegates return.
+    pub const SPAWN: u32 = 0x01;
+    /// The component cell data is a ComponentData which attaches the value to an entity.
+    pub const ENTITY: u32 = 0x02;
+    /// Requires a TypedMessage in the witness, see `MessageField`.
+    pub const MESSAGE_REQUIRED: u32 = 0x04;
+    /// The component data cannot change once created. Group outputs are paired with group inputs
+    /// by index, and a paired output must keep the data of its input.
+    pub const IMMUTABLE: u32 = 0x08;
+    /// The component cannot be destroyed, i.e., each group input must have a paired output.
+    pub const NO_DESTROY: u32 = 0x10;
+    /// The created or updated components must set TYPE_ID in ComponentArgsV2, so there's at most
+    /// one live component per (definition, entity). See `component_flags::TYPE_ID`.
+    pub const TYPE_ID: u32 = 0x20;
+}
+
+/// Semantic version as (major, minor, patch). V1 definitions are version 0.0.0.
+pub type Version = (u32, u32, u32);
+
+/// Accessors shared by all the ComponentDefinition variants.
+impl<'r> ComponentDefinitionReader<'r> {
+    pub fn component_name(&self) -> StringReader<'r> {
+        match self.to_enum() {
+            ComponentDefinitionV1(v1) => v1.component_name(),
+            ComponentDefinitionV2(v2) => v2.component_name(),
+        }
+    }
+
+    pub fn info_hash(&self) -> Byte32Reader<'r> {
+        match self.to_enum() {
+            ComponentDefinitionV1(v1) => v1.info_hash(),
+            ComponentDefinitionV2(v2) => v2.info_hash(),
+        }
+    }
+
+    /// Returns None if the value schema hash is not specified.
+    pub fn value_schema_hash(&self) -> Option<Byte32Reader<'r>> {
+        match self.to_enum() {
+            ComponentDefinitionV1(_) => None,
+            ComponentDefinitionV2(v2) => {
+                let hash = v2.value_schema_hash();
+                if hash.raw_data().iter().all(|b| *b == 0) {
+                    None
+                } else {
+                    Some(hash)
+                }
+            }
+        }
+    }
+
+    pub fn version(&self) -> Version {
+        match self.to_enum() {
+            ComponentDefinitionV1(_) => (0, 0, 0),
+            ComponentDefinitionV2(v2) => {
+                let version = v2.version();
+                (
+                    version.major().into(),
+                    version.minor().into(),
+                    version.patch().into(),
+                )
+            }
+        }
+    }
+
+    pub fn delegates_len(&self) -> usize {
+        match self.to_enum() {
+            ComponentDefinitionV1(_) => 1,
+            ComponentDefinitionV2(v2) => v2.delegates().len(),
+        }
+    }
+
+    pub fn delegate(&self, index: usize) -> Option<ScriptReader<'r>> {
+        match self.to_enum() {
+            ComponentDefinitionV1(v1) if index == 0 => Some(v1.delegate()),
+            ComponentDefinitionV1(_) => None,
+            ComponentDefinitionV2(v2) => v2.delegates().get(index),
+        }
+    }
+
+    pub fn flags(&self) -> u32 {
+        match self.to_enum() {
+            ComponentDefinitionV1(_) => 0,
+            ComponentDefinitionV2(v2) => v2.flags().into(),
+        }
+    }
+
+    /// Returns None for V1, which has no system restrictions.
+    pub fn systems(&self) -> Option<Byte32VecReader<'r>> {
+        match self.to_enum() {
+            ComponentDefinitionV1(_) => None,
+            ComponentDefinitionV2(v2) => Some(v2.systems()),
+        }
+    }
+
+    /// Returns None for V1 or if the authority is absent, and the definition is immutable then.
+    pub fn authority(&self) -> Option<Byte32Reader<'r>> {
+        match self.to_enum() {
+            ComponentDefinitionV1(_) => None,
+            ComponentDefinitionV2(v2) => v2.authority().to_opt(),
+        }
+    }
+
+    /// Returns None for V1 or if the entity type is absent.
+    pub fn entity_type(&self) -> Option<EntityTypeReader<'r>> {
+        match self.to_enum() {
+            ComponentDefinitionV1(_) => None,
+            ComponentDefinitionV2(v2) => v2.entity_type().to_opt(),
+        }
+    }
+}
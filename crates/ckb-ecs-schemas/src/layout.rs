@@ -0,0 +1,287 @@
+//! Verifies that a component value matches its ValueSchema.
+use crate::schemas::{LayoutNodeReader, ValueSchemaReader};
+use alloc::vec::Vec;
+use molecule::prelude::*;
+
+pub const KIND_FIXED: u8 = 0;
+pub const KIND_STRUCT: u8 = 1;
+pub const KIND_FIXVEC: u8 = 2;
+pub const KIND_DYNVEC: u8 = 3;
+pub const KIND_TABLE: u8 = 4;
+pub const KIND_OPTION: u8 = 5;
+pub const KIND_UNION: u8 = 6;
+
+/// The schema is a tree stored in a flat list, the depth limit also rejects cyclic schemas.
+pub const MAX_DEPTH: usize = 16;
+
+const NUMBER_SIZE: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LayoutError {
+    InvalidSchema,
+    InvalidData,
+    TooDeep,
+}
+
+pub fn verify_value(schema: ValueSchemaReader, data: &[u8]) -> Result<(), LayoutError> {
+    verify_node(&schema, 0, data, 0)
+}
+
+fn node<'r>(
+    schema: &ValueSchemaReader<'r>,
+    index: usize,
+) -> Result<LayoutNodeReader<'r>, LayoutError> {
+    schema.nodes().get(index).ok_or(LayoutError::InvalidSchema)
+}
+
+fn child(
+    schema: &ValueSchemaReader,
+    node: &LayoutNodeReader,
+    nth: usize,
+) -> Result<usize, LayoutError> {
+    let offset = u32::from(node.offset()) as usize;
+    let index = offset.checked_add(nth).ok_or(LayoutError::InvalidSchema)?;
+    schema
+        .children()
+        .get(index)
+        .map(|index| u32::from(index) as usize)
+        .ok_or(LayoutError::InvalidSchema)
+}
+
+fn read_number(data: &[u8]) -> Result<usize, LayoutError> {
+    if data.len() < NUMBER_SIZE {
+        return Err(LayoutError::InvalidData);
+    }
+    let mut buf = [0u8; NUMBER_SIZE];
+    buf.copy_from_slice(&data[..NUMBER_SIZE]);
+    Ok(u32::from_le_bytes(buf) as usize)
+}
+
+// Returns the size if the node is fixed sized.
+fn fixed_size(
+    schema: &ValueSchemaReader,
+    index: usize,
+    depth: usize,
+) -> Result<Option<usize>, LayoutError> {
+    if depth > MAX_DEPTH {
+        return Err(LayoutError::TooDeep);
+    }
+    let node = node(schema, index)?;
+    let count = u32::from(node.count()) as usize;
+    match node.kind().as_slice()[0] {
+        KIND_FIXED => Ok(Some(count)),
+        KIND_STRUCT => {
+            let mut size = 0usize;
+            for i in 0..count {
+                let field_size = fixed_size(schema, child(schema, &node, i)?, depth + 1)?
+                    .ok_or(LayoutError::InvalidSchema)?;
+                size = size
+                    .checked_add(field_size)
+                    .ok_or(LayoutError::InvalidSchema)?;
+            }
+            Ok(Some(size))
+        }
+        KIND_FIXVEC | KIND_DYNVEC | KIND_TABLE | KIND_OPTION | KIND_UNION => Ok(None),
+        _ => Err(LayoutError::InvalidSchema),
+    }
+}
+
+// Splits the items of a dynvec or a table by the header offsets.
+fn split_items(data: &[u8]) -> Result<Vec<&[u8]>, LayoutError> {
+    let total_size = read_number(data)?;
+    if total_size != data.len() {
+        return Err(LayoutError::InvalidData);
+    }
+    if total_size == NUMBER_SIZE {
+        return Ok(Vec::new());
+    }
+
+    let first_offset = read_number(&data[NUMBER_SIZE..])?;
+    if first_offset % NUMBER_SIZE != 0
+        || first_offset < NUMBER_SIZE * 2
+        || first_offset > total_size
+    {
+        return Err(LayoutError::InvalidData);
+    }
+    let items_count = first_offset / NUMBER_SIZE - 1;
+
+    let mut offsets = Vec::with_capacity(items_count + 1);
+    for i in 0..items_count {
+        offsets.push(read_number(&data[NUMBER_SIZE * (i + 1)..])?);
+    }
+    offsets.push(total_size);
+
+    let mut items = Vec::with_capacity(items_count);
+    for window in offsets.windows(2) {
+        if window[0] > window[1] {
+            return Err(LayoutError::InvalidData);
+        }
+        items.push(&data[window[0]..window[1]]);
+    }
+    Ok(items)
+}
+
+fn verify_node(
+    schema: &ValueSchemaReader,
+    index: usize,
+    data: &[u8],
+    depth: usize,
+) -> Result<(), LayoutError> {
+    if depth > MAX_DEPTH {
+        return Err(LayoutError::TooDeep);
+    }
+    let node = node(schema, index)?;
+    let count = u32::from(node.count()) as usize;
+    match node.kind().as_slice()[0] {
+        KIND_FIXED | KIND_STRUCT => {
+            let size = fixed_size(schema, index, depth)?.ok_or(LayoutError::InvalidSchema)?;
+            if data.len() != size {
+                return Err(LayoutError::InvalidData);
+            }
+        }
+        KIND_FIXVEC => {
+            let item_size = fixed_size(schema, child(schema, &node, 0)?, depth + 1)?
+                .ok_or(LayoutError::InvalidSchema)?;
+            let items_count = read_number(data)?;
+            let expected_size = items_count
+                .checked_mul(item_size)
+                .and_then(|size| size.checked_add(NUMBER_SIZE))
+                .ok_or(LayoutError::InvalidData)?;
+            if data.len() != expected_size {
+                return Err(LayoutError::InvalidData);
+            }
+        }
+        KIND_DYNVEC => {
+            let item = child(schema, &node, 0)?;
+            for item_data in split_items(data)? {
+                verify_node(schema, item, item_data, depth + 1)?;
+            }
+        }
+        KIND_TABLE => {
+            let fields = split_items(data)?;
+            if fields.len() != count {
+                return Err(LayoutError::InvalidData);
+            }
+            for (i, field_data) in fields.into_iter().enumerate() {
+                verify_node(schema, child(schema, &node, i)?, field_data, depth + 1)?;
+            }
+        }
+        KIND_OPTION => {
+            if !data.is_empty() {
+                verify_node(schema, child(schema, &node, 0)?, data, depth + 1)?;
+            }
+        }
+        KIND_UNION => {
+            let item_id = read_number(data)?;
+            if item_id >= count {
+                return Err(LayoutError::InvalidData);
+            }
+            verify_node(
+                schema,
+                child(schema, &node, item_id)?,
+                &data[NUMBER_SIZE..],
+                depth + 1,
+            )?;
+        }
+        _ => return Err(LayoutError::InvalidSchema),
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+fn build_schema(nodes: &[(u8, u32, u32)], children: &[u32]) -> crate::schemas::ValueSchema {
+    use crate::schemas::*;
+
+    let nodes = LayoutNodeVecBuilder::default()
+        .extend(nodes.iter().map(|(kind, offset, count)| {
+            LayoutNodeBuilder::default()
+                .kind((*kind).into())
+                .offset((*offset).into())
+                .count((*count).into())
+                .build()
+        }))
+        .build();
+    let children = Uint32VecBuilder::default()
+        .extend(children.iter().map(|index| (*index).into()))
+        .build();
+    ValueSchemaBuilder::default()
+        .nodes(nodes)
+        .children(children)
+        .build()
+}
+
+#[test]
+fn verify_dapp_info_value() {
+    use crate::schemas::*;
+
+    // table DappInfo { name: String, version: Uint32, components: ComponentInfoVec, owner: Byte32 }
+    let schema = build_schema(
+        &[
+            (KIND_TABLE, 0, 4),
+            (KIND_FIXVEC, 4, 0),
+            (KIND_FIXED, 0, 4),
+            (KIND_DYNVEC, 5, 0),
+            (KIND_FIXED, 0, 32),
+            (KIND_FIXED, 0, 1),
+            (KIND_TABLE, 6, 2),
+        ],
+        &[1, 2, 3, 4, 5, 6, 1, 1],
+    );
+    let components = ComponentInfoVecBuilder::default()
+        .push(
+            ComponentInfoBuilder::default()
+                .name("position".into())
+                .schema((&[1u8, 2, 3] as &[u8]).into())
+                .build(),
+        )
+        .build();
+    let value = DappInfoBuilder::default()
+        .name("dapp".into())
+        .version(1u32.into())
+        .components(components)
+        .build();
+    assert_eq!(verify_value(schema.as_reader(), value.as_slice()), Ok(()));
+
+    let mut data = value.as_slice().to_vec();
+    data.push(0);
+    assert_eq!(
+        verify_value(schema.as_reader(), &data),
+        Err(LayoutError::InvalidData)
+    );
+}
+
+#[test]
+fn verify_union_and_option_value() {
+    use crate::schemas::*;
+
+    // union { Byte32, Byte32Opt }
+    let schema = build_schema(
+        &[(KIND_UNION, 0, 2), (KIND_FIXED, 0, 32), (KIND_OPTION, 0, 0)],
+        &[1, 2],
+    );
+
+    let mut data = 1u32.to_le_bytes().to_vec();
+    assert_eq!(verify_value(schema.as_reader(), &data), Ok(()));
+    data.extend_from_slice(&[0u8; 32]);
+    assert_eq!(verify_value(schema.as_reader(), &data), Ok(()));
+
+    data[0] = 0;
+    assert_eq!(verify_value(schema.as_reader(), &data), Ok(()));
+    data[0] = 2;
+    assert_eq!(
+        verify_value(schema.as_reader(), &data),
+        Err(LayoutError::InvalidData)
+    );
+}
+
+#[test]
+fn verify_cyclic_schema() {
+    // An option which contains itself
+    let schema = build_schema(&[(KIND_OPTION, 0, 0)], &[0]);
+    let data = [0u8; 4];
+    assert_eq!(
+        verify_value(schema.as_reader(), &data),
+        Err(LayoutError::TooDeep)
+    );
+}
@@ -1,8 +1,18 @@
 #![no_std]
 extern crate alloc;
 
+mod component_args;
 mod conversion;
+mod definition;
+pub mod layout;
+mod message;
 mod schemas;
+#[cfg(feature = "ckb-std")]
+pub mod type_id;
+pub mod typed_hash;
 
+pub use component_args::{flags as component_flags, VersionedComponentArgsReader};
+pub use definition::{flags as definition_flags, Version};
+pub use message::MessageField;
 pub use molecule::prelude;
 pub use schemas::*;
@@ -0,0 +1,70 @@
+//! Places TypedMessage in the witness of the component-type script group.
+use crate::schemas::{
+    BytesOpt, TypedMessage, TypedMessageReader, WitnessArgs, WitnessArgsBuilder, WitnessArgsReader,
+};
+use molecule::{error::VerificationError, prelude::*};
+
+/// The WitnessArgs field which carries the message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageField {
+    /// Used when the script group has inputs.
+    InputType,
+    /// Used when the script group only has outputs.
+    OutputType,
+}
+
+impl TypedMessage {
+    pub fn to_witness(&self, field: MessageField) -> WitnessArgs {
+        let bytes = BytesOpt::new_builder()
+            .set(Some(self.as_slice().into()))
+            .build();
+        let builder = WitnessArgsBuilder::default();
+        match field {
+            MessageField::InputType => builder.input_type(bytes),
+            MessageField::OutputType => builder.output_type(bytes),
+        }
+        .build()
+    }
+}
+
+impl<'r> TypedMessageReader<'r> {
+    /// Returns None if the witness does not have the message field.
+    pub fn from_witness(
+        witness: &'r [u8],
+        field: MessageField,
+    ) -> Result<Option<Self>, VerificationError> {
+        let witness_args = WitnessArgsReader::from_slice(witness)?;
+        let bytes = match field {
+            MessageField::InputType => witness_args.input_type(),
+            MessageField::OutputType => witness_args.output_type(),
+        };
+        bytes
+            .to_opt()
+            .map(|bytes| TypedMessageReader::from_slice(bytes.raw_data()))
+            .transpose()
+    }
+}
+
+#[test]
+fn typed_message_witness_roundtrip() {
+    use crate::schemas::TypedMessageBuilder;
+
+    let message = TypedMessageBuilder::default()
+        .action("move".into())
+        .target([1u8; 32].into())
+        .payload((&[1u8, 2, 3] as &[u8]).into())
+        .nonce(7u64.into())
+        .build();
+    let witness = message.to_witness(MessageField::OutputType);
+
+    let reader = TypedMessageReader::from_witness(witness.as_slice(), MessageField::OutputType)
+        .expect("valid witness")
+        .expect("message exists");
+    assert_eq!(reader.as_slice(), message.as_slice());
+    assert_eq!(u64::from(reader.nonce()), 7);
+    assert!(
+        TypedMessageReader::from_witness(witness.as_slice(), MessageField::InputType)
+            .expect("valid witness")
+            .is_none()
+    );
+}
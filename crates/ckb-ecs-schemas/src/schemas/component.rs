@@ -1,6 +1,7 @@
 // Generated by Molecule 0.7.5
 
 use super::blockchain::*;
+use super::entity::*;
 use molecule::prelude::*;
 #[derive(Clone)]
 pub struct ComponentArgs(molecule::bytes::Bytes);
@@ -169,3 +170,201 @@ impl molecule::prelude::Builder for ComponentArgsBuilder {
         ComponentArgs::new_unchecked(inner.into())
     }
 }
+#[derive(Clone)]
+pub struct ComponentArgsV2(molecule::bytes::Bytes);
+impl ::core::fmt::LowerHex for ComponentArgsV2 {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        use molecule::hex_string;
+        if f.alternate() {
+            write!(f, "0x")?;
+        }
+        write!(f, "{}", hex_string(self.as_slice()))
+    }
+}
+impl ::core::fmt::Debug for ComponentArgsV2 {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{}({:#x})", Self::NAME, self)
+    }
+}
+impl ::core::fmt::Display for ComponentArgsV2 {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{} {{ ", Self::NAME)?;
+        write!(f, "{}: {}", "code_hash", self.code_hash())?;
+        write!(f, ", {}: {}", "hash_type", self.hash_type())?;
+        write!(f, ", {}: {}", "entity", self.entity())?;
+        write!(f, ", {}: {}", "flags", self.flags())?;
+        write!(f, " }}")
+    }
+}
+impl ::core::default::Default for ComponentArgsV2 {
+    fn default() -> Self {
+        let v = molecule::bytes::Bytes::from_static(&Self::DEFAULT_VALUE);
+        ComponentArgsV2::new_unchecked(v)
+    }
+}
+impl ComponentArgsV2 {
+    const DEFAULT_VALUE: [u8; 66] = [
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0,
+    ];
+    pub const TOTAL_SIZE: usize = 66;
+    pub const FIELD_SIZES: [usize; 4] = [32, 1, 32, 1];
+    pub const FIELD_COUNT: usize = 4;
+    pub fn code_hash(&self) -> Byte32 {
+        Byte32::new_unchecked(self.0.slice(0..32))
+    }
+    pub fn hash_type(&self) -> Byte {
+        Byte::new_unchecked(self.0.slice(32..33))
+    }
+    pub fn entity(&self) -> EntityId {
+        EntityId::new_unchecked(self.0.slice(33..65))
+    }
+    pub fn flags(&self) -> Byte {
+        Byte::new_unchecked(self.0.slice(65..66))
+    }
+    pub fn as_reader<'r>(&'r self) -> ComponentArgsV2Reader<'r> {
+        ComponentArgsV2Reader::new_unchecked(self.as_slice())
+    }
+}
+impl molecule::prelude::Entity for ComponentArgsV2 {
+    type Builder = ComponentArgsV2Builder;
+    const NAME: &'static str = "ComponentArgsV2";
+    fn new_unchecked(data: molecule::bytes::Bytes) -> Self {
+        ComponentArgsV2(data)
+    }
+    fn as_bytes(&self) -> molecule::bytes::Bytes {
+        self.0.clone()
+    }
+    fn as_slice(&self) -> &[u8] {
+        &self.0[..]
+    }
+    fn from_slice(slice: &[u8]) -> molecule::error::VerificationResult<Self> {
+        ComponentArgsV2Reader::from_slice(slice).map(|reader| reader.to_entity())
+    }
+    fn from_compatible_slice(slice: &[u8]) -> molecule::error::VerificationResult<Self> {
+        ComponentArgsV2Reader::from_compatible_slice(slice).map(|reader| reader.to_entity())
+    }
+    fn new_builder() -> Self::Builder {
+        ::core::default::Default::default()
+    }
+    fn as_builder(self) -> Self::Builder {
+        Self::new_builder()
+            .code_hash(self.code_hash())
+            .hash_type(self.hash_type())
+            .entity(self.entity())
+            .flags(self.flags())
+    }
+}
+#[derive(Clone, Copy)]
+pub struct ComponentArgsV2Reader<'r>(&'r [u8]);
+impl<'r> ::core::fmt::LowerHex for ComponentArgsV2Reader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        use molecule::hex_string;
+        if f.alternate() {
+            write!(f, "0x")?;
+        }
+        write!(f, "{}", hex_string(self.as_slice()))
+    }
+}
+impl<'r> ::core::fmt::Debug for ComponentArgsV2Reader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{}({:#x})", Self::NAME, self)
+    }
+}
+impl<'r> ::core::fmt::Display for ComponentArgsV2Reader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{} {{ ", Self::NAME)?;
+        write!(f, "{}: {}", "code_hash", self.code_hash())?;
+        write!(f, ", {}: {}", "hash_type", self.hash_type())?;
+        write!(f, ", {}: {}", "entity", self.entity())?;
+        write!(f, ", {}: {}", "flags", self.flags())?;
+        write!(f, " }}")
+    }
+}
+impl<'r> ComponentArgsV2Reader<'r> {
+    pub const TOTAL_SIZE: usize = 66;
+    pub const FIELD_SIZES: [usize; 4] = [32, 1, 32, 1];
+    pub const FIELD_COUNT: usize = 4;
+    pub fn code_hash(&self) -> Byte32Reader<'r> {
+        Byte32Reader::new_unchecked(&self.as_slice()[0..32])
+    }
+    pub fn hash_type(&self) -> ByteReader<'r> {
+        ByteReader::new_unchecked(&self.as_slice()[32..33])
+    }
+    pub fn entity(&self) -> EntityIdReader<'r> {
+        EntityIdReader::new_unchecked(&self.as_slice()[33..65])
+    }
+    pub fn flags(&self) -> ByteReader<'r> {
+        ByteReader::new_unchecked(&self.as_slice()[65..66])
+    }
+}
+impl<'r> molecule::prelude::Reader<'r> for ComponentArgsV2Reader<'r> {
+    type Entity = ComponentArgsV2;
+    const NAME: &'static str = "ComponentArgsV2Reader";
+    fn to_entity(&self) -> Self::Entity {
+        Self::Entity::new_unchecked(self.as_slice().to_owned().into())
+    }
+    fn new_unchecked(slice: &'r [u8]) -> Self {
+        ComponentArgsV2Reader(slice)
+    }
+    fn as_slice(&self) -> &'r [u8] {
+        self.0
+    }
+    fn verify(slice: &[u8], _compatible: bool) -> molecule::error::VerificationResult<()> {
+        use molecule::verification_error as ve;
+        let slice_len = slice.len();
+        if slice_len != Self::TOTAL_SIZE {
+            return ve!(Self, TotalSizeNotMatch, Self::TOTAL_SIZE, slice_len);
+        }
+        Ok(())
+    }
+}
+#[derive(Debug, Default)]
+pub struct ComponentArgsV2Builder {
+    pub(crate) code_hash: Byte32,
+    pub(crate) hash_type: Byte,
+    pub(crate) entity: EntityId,
+    pub(crate) flags: Byte,
+}
+impl ComponentArgsV2Builder {
+    pub const TOTAL_SIZE: usize = 66;
+    pub const FIELD_SIZES: [usize; 4] = [32, 1, 32, 1];
+    pub const FIELD_COUNT: usize = 4;
+    pub fn code_hash(mut self, v: Byte32) -> Self {
+        self.code_hash = v;
+        self
+    }
+    pub fn hash_type(mut self, v: Byte) -> Self {
+        self.hash_type = v;
+        self
+    }
+    pub fn entity(mut self, v: EntityId) -> Self {
+        self.entity = v;
+        self
+    }
+    pub fn flags(mut self, v: Byte) -> Self {
+        self.flags = v;
+        self
+    }
+}
+impl molecule::prelude::Builder for ComponentArgsV2Builder {
+    type Entity = ComponentArgsV2;
+    const NAME: &'static str = "ComponentArgsV2Builder";
+    fn expected_length(&self) -> usize {
+        Self::TOTAL_SIZE
+    }
+    fn write<W: molecule::io::Write>(&self, writer: &mut W) -> molecule::io::Result<()> {
+        writer.write_all(self.code_hash.as_slice())?;
+        writer.write_all(self.hash_type.as_slice())?;
+        writer.write_all(self.entity.as_slice())?;
+        writer.write_all(self.flags.as_slice())?;
+        Ok(())
+    }
+    fn build(&self) -> Self::Entity {
+        let mut inner = Vec::with_capacity(self.expected_length());
+        self.write(&mut inner)
+            .unwrap_or_else(|_| panic!("{} build should be ok", Self::NAME));
+        ComponentArgsV2::new_unchecked(inner.into())
+    }
+}
@@ -1,6 +1,7 @@
 // Generated by Molecule 0.7.5
 
 use super::blockchain::*;
+use super::entity::*;
 use molecule::prelude::*;
 #[derive(Clone)]
 pub struct String(molecule::bytes::Bytes);
@@ -536,6 +537,626 @@ impl molecule::prelude::Builder for ComponentDefinitionV1Builder {
     }
 }
 #[derive(Clone)]
+pub struct SemVer(molecule::bytes::Bytes);
+impl ::core::fmt::LowerHex for SemVer {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        use molecule::hex_string;
+        if f.alternate() {
+            write!(f, "0x")?;
+        }
+        write!(f, "{}", hex_string(self.as_slice()))
+    }
+}
+impl ::core::fmt::Debug for SemVer {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{}({:#x})", Self::NAME, self)
+    }
+}
+impl ::core::fmt::Display for SemVer {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{} {{ ", Self::NAME)?;
+        write!(f, "{}: {}", "major", self.major())?;
+        write!(f, ", {}: {}", "minor", self.minor())?;
+        write!(f, ", {}: {}", "patch", self.patch())?;
+        write!(f, " }}")
+    }
+}
+impl ::core::default::Default for SemVer {
+    fn default() -> Self {
+        let v = molecule::bytes::Bytes::from_static(&Self::DEFAULT_VALUE);
+        SemVer::new_unchecked(v)
+    }
+}
+impl SemVer {
+    const DEFAULT_VALUE: [u8; 12] = [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+    pub const TOTAL_SIZE: usize = 12;
+    pub const FIELD_SIZES: [usize; 3] = [4, 4, 4];
+    pub const FIELD_COUNT: usize = 3;
+    pub fn major(&self) -> Uint32 {
+        Uint32::new_unchecked(self.0.slice(0..4))
+    }
+    pub fn minor(&self) -> Uint32 {
+        Uint32::new_unchecked(self.0.slice(4..8))
+    }
+    pub fn patch(&self) -> Uint32 {
+        Uint32::new_unchecked(self.0.slice(8..12))
+    }
+    pub fn as_reader<'r>(&'r self) -> SemVerReader<'r> {
+        SemVerReader::new_unchecked(self.as_slice())
+    }
+}
+impl molecule::prelude::Entity for SemVer {
+    type Builder = SemVerBuilder;
+    const NAME: &'static str = "SemVer";
+    fn new_unchecked(data: molecule::bytes::Bytes) -> Self {
+        SemVer(data)
+    }
+    fn as_bytes(&self) -> molecule::bytes::Bytes {
+        self.0.clone()
+    }
+    fn as_slice(&self) -> &[u8] {
+        &self.0[..]
+    }
+    fn from_slice(slice: &[u8]) -> molecule::error::VerificationResult<Self> {
+        SemVerReader::from_slice(slice).map(|reader| reader.to_entity())
+    }
+    fn from_compatible_slice(slice: &[u8]) -> molecule::error::VerificationResult<Self> {
+        SemVerReader::from_compatible_slice(slice).map(|reader| reader.to_entity())
+    }
+    fn new_builder() -> Self::Builder {
+        ::core::default::Default::default()
+    }
+    fn as_builder(self) -> Self::Builder {
+        Self::new_builder()
+            .major(self.major())
+            .minor(self.minor())
+            .patch(self.patch())
+    }
+}
+#[derive(Clone, Copy)]
+pub struct SemVerReader<'r>(&'r [u8]);
+impl<'r> ::core::fmt::LowerHex for SemVerReader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        use molecule::hex_string;
+        if f.alternate() {
+            write!(f, "0x")?;
+        }
+        write!(f, "{}", hex_string(self.as_slice()))
+    }
+}
+impl<'r> ::core::fmt::Debug for SemVerReader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{}({:#x})", Self::NAME, self)
+    }
+}
+impl<'r> ::core::fmt::Display for SemVerReader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{} {{ ", Self::NAME)?;
+        write!(f, "{}: {}", "major", self.major())?;
+        write!(f, ", {}: {}", "minor", self.minor())?;
+        write!(f, ", {}: {}", "patch", self.patch())?;
+        write!(f, " }}")
+    }
+}
+impl<'r> SemVerReader<'r> {
+    pub const TOTAL_SIZE: usize = 12;
+    pub const FIELD_SIZES: [usize; 3] = [4, 4, 4];
+    pub const FIELD_COUNT: usize = 3;
+    pub fn major(&self) -> Uint32Reader<'r> {
+        Uint32Reader::new_unchecked(&self.as_slice()[0..4])
+    }
+    pub fn minor(&self) -> Uint32Reader<'r> {
+        Uint32Reader::new_unchecked(&self.as_slice()[4..8])
+    }
+    pub fn patch(&self) -> Uint32Reader<'r> {
+        Uint32Reader::new_unchecked(&self.as_slice()[8..12])
+    }
+}
+impl<'r> molecule::prelude::Reader<'r> for SemVerReader<'r> {
+    type Entity = SemVer;
+    const NAME: &'static str = "SemVerReader";
+    fn to_entity(&self) -> Self::Entity {
+        Self::Entity::new_unchecked(self.as_slice().to_owned().into())
+    }
+    fn new_unchecked(slice: &'r [u8]) -> Self {
+        SemVerReader(slice)
+    }
+    fn as_slice(&self) -> &'r [u8] {
+        self.0
+    }
+    fn verify(slice: &[u8], _compatible: bool) -> molecule::error::VerificationResult<()> {
+        use molecule::verification_error as ve;
+        let slice_len = slice.len();
+        if slice_len != Self::TOTAL_SIZE {
+            return ve!(Self, TotalSizeNotMatch, Self::TOTAL_SIZE, slice_len);
+        }
+        Ok(())
+    }
+}
+#[derive(Debug, Default)]
+pub struct SemVerBuilder {
+    pub(crate) major: Uint32,
+    pub(crate) minor: Uint32,
+    pub(crate) patch: Uint32,
+}
+impl SemVerBuilder {
+    pub const TOTAL_SIZE: usize = 12;
+    pub const FIELD_SIZES: [usize; 3] = [4, 4, 4];
+    pub const FIELD_COUNT: usize = 3;
+    pub fn major(mut self, v: Uint32) -> Self {
+        self.major = v;
+        self
+    }
+    pub fn minor(mut self, v: Uint32) -> Self {
+        self.minor = v;
+        self
+    }
+    pub fn patch(mut self, v: Uint32) -> Self {
+        self.patch = v;
+        self
+    }
+}
+impl molecule::prelude::Builder for SemVerBuilder {
+    type Entity = SemVer;
+    const NAME: &'static str = "SemVerBuilder";
+    fn expected_length(&self) -> usize {
+        Self::TOTAL_SIZE
+    }
+    fn write<W: molecule::io::Write>(&self, writer: &mut W) -> molecule::io::Result<()> {
+        writer.write_all(self.major.as_slice())?;
+        writer.write_all(self.minor.as_slice())?;
+        writer.write_all(self.patch.as_slice())?;
+        Ok(())
+    }
+    fn build(&self) -> Self::Entity {
+        let mut inner = Vec::with_capacity(self.expected_length());
+        self.write(&mut inner)
+            .unwrap_or_else(|_| panic!("{} build should be ok", Self::NAME));
+        SemVer::new_unchecked(inner.into())
+    }
+}
+#[derive(Clone)]
+pub struct ComponentDefinitionV2(molecule::bytes::Bytes);
+impl ::core::fmt::LowerHex for ComponentDefinitionV2 {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        use molecule::hex_string;
+        if f.alternate() {
+            write!(f, "0x")?;
+        }
+        write!(f, "{}", hex_string(self.as_slice()))
+    }
+}
+impl ::core::fmt::Debug for ComponentDefinitionV2 {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{}({:#x})", Self::NAME, self)
+    }
+}
+impl ::core::fmt::Display for ComponentDefinitionV2 {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{} {{ ", Self::NAME)?;
+        write!(f, "{}: {}", "component_name", self.component_name())?;
+        write!(f, ", {}: {}", "info_hash", self.info_hash())?;
+        write!(f, ", {}: {}", "value_schema_hash", self.value_schema_hash())?;
+        write!(f, ", {}: {}", "version", self.version())?;
+        write!(f, ", {}: {}", "delegates", self.delegates())?;
+        write!(f, ", {}: {}", "flags", self.flags())?;
+        write!(f, ", {}: {}", "systems", self.systems())?;
+        write!(f, ", {}: {}", "authority", self.authority())?;
+        write!(f, ", {}: {}", "entity_type", self.entity_type())?;
+        let extra_count = self.count_extra_fields();
+        if extra_count != 0 {
+            write!(f, ", .. ({} fields)", extra_count)?;
+        }
+        write!(f, " }}")
+    }
+}
+impl ::core::default::Default for ComponentDefinitionV2 {
+    fn default() -> Self {
+        let v = molecule::bytes::Bytes::from_static(&Self::DEFAULT_VALUE);
+        ComponentDefinitionV2::new_unchecked(v)
+    }
+}
+impl ComponentDefinitionV2 {
+    const DEFAULT_VALUE: [u8; 132] = [
+        132, 0, 0, 0, 40, 0, 0, 0, 44, 0, 0, 0, 76, 0, 0, 0, 108, 0, 0, 0, 120, 0, 0, 0, 124, 0, 0,
+        0, 128, 0, 0, 0, 132, 0, 0, 0, 132, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 4, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    ];
+    pub const FIELD_COUNT: usize = 9;
+    pub fn total_size(&self) -> usize {
+        molecule::unpack_number(self.as_slice()) as usize
+    }
+    pub fn field_count(&self) -> usize {
+        if self.total_size() == molecule::NUMBER_SIZE {
+            0
+        } else {
+            (molecule::unpack_number(&self.as_slice()[molecule::NUMBER_SIZE..]) as usize / 4) - 1
+        }
+    }
+    pub fn count_extra_fields(&self) -> usize {
+        self.field_count() - Self::FIELD_COUNT
+    }
+    pub fn has_extra_fields(&self) -> bool {
+        Self::FIELD_COUNT != self.field_count()
+    }
+    pub fn component_name(&self) -> String {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[4..]) as usize;
+        let end = molecule::unpack_number(&slice[8..]) as usize;
+        String::new_unchecked(self.0.slice(start..end))
+    }
+    pub fn info_hash(&self) -> Byte32 {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[8..]) as usize;
+        let end = molecule::unpack_number(&slice[12..]) as usize;
+        Byte32::new_unchecked(self.0.slice(start..end))
+    }
+    pub fn value_schema_hash(&self) -> Byte32 {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[12..]) as usize;
+        let end = molecule::unpack_number(&slice[16..]) as usize;
+        Byte32::new_unchecked(self.0.slice(start..end))
+    }
+    pub fn version(&self) -> SemVer {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[16..]) as usize;
+        let end = molecule::unpack_number(&slice[20..]) as usize;
+        SemVer::new_unchecked(self.0.slice(start..end))
+    }
+    pub fn delegates(&self) -> ScriptVec {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[20..]) as usize;
+        let end = molecule::unpack_number(&slice[24..]) as usize;
+        ScriptVec::new_unchecked(self.0.slice(start..end))
+    }
+    pub fn flags(&self) -> Uint32 {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[24..]) as usize;
+        let end = molecule::unpack_number(&slice[28..]) as usize;
+        Uint32::new_unchecked(self.0.slice(start..end))
+    }
+    pub fn systems(&self) -> Byte32Vec {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[28..]) as usize;
+        let end = molecule::unpack_number(&slice[32..]) as usize;
+        Byte32Vec::new_unchecked(self.0.slice(start..end))
+    }
+    pub fn authority(&self) -> Byte32Opt {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[32..]) as usize;
+        let end = molecule::unpack_number(&slice[36..]) as usize;
+        Byte32Opt::new_unchecked(self.0.slice(start..end))
+    }
+    pub fn entity_type(&self) -> EntityTypeOpt {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[36..]) as usize;
+        if self.has_extra_fields() {
+            let end = molecule::unpack_number(&slice[40..]) as usize;
+            EntityTypeOpt::new_unchecked(self.0.slice(start..end))
+        } else {
+            EntityTypeOpt::new_unchecked(self.0.slice(start..))
+        }
+    }
+    pub fn as_reader<'r>(&'r self) -> ComponentDefinitionV2Reader<'r> {
+        ComponentDefinitionV2Reader::new_unchecked(self.as_slice())
+    }
+}
+impl molecule::prelude::Entity for ComponentDefinitionV2 {
+    type Builder = ComponentDefinitionV2Builder;
+    const NAME: &'static str = "ComponentDefinitionV2";
+    fn new_unchecked(data: molecule::bytes::Bytes) -> Self {
+        ComponentDefinitionV2(data)
+    }
+    fn as_bytes(&self) -> molecule::bytes::Bytes {
+        self.0.clone()
+    }
+    fn as_slice(&self) -> &[u8] {
+        &self.0[..]
+    }
+    fn from_slice(slice: &[u8]) -> molecule::error::VerificationResult<Self> {
+        ComponentDefinitionV2Reader::from_slice(slice).map(|reader| reader.to_entity())
+    }
+    fn from_compatible_slice(slice: &[u8]) -> molecule::error::VerificationResult<Self> {
+        ComponentDefinitionV2Reader::from_compatible_slice(slice).map(|reader| reader.to_entity())
+    }
+    fn new_builder() -> Self::Builder {
+        ::core::default::Default::default()
+    }
+    fn as_builder(self) -> Self::Builder {
+        Self::new_builder()
+            .component_name(self.component_name())
+            .info_hash(self.info_hash())
+            .value_schema_hash(self.value_schema_hash())
+            .version(self.version())
+            .delegates(self.delegates())
+            .flags(self.flags())
+            .systems(self.systems())
+            .authority(self.authority())
+            .entity_type(self.entity_type())
+    }
+}
+#[derive(Clone, Copy)]
+pub struct ComponentDefinitionV2Reader<'r>(&'r [u8]);
+impl<'r> ::core::fmt::LowerHex for ComponentDefinitionV2Reader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        use molecule::hex_string;
+        if f.alternate() {
+            write!(f, "0x")?;
+        }
+        write!(f, "{}", hex_string(self.as_slice()))
+    }
+}
+impl<'r> ::core::fmt::Debug for ComponentDefinitionV2Reader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{}({:#x})", Self::NAME, self)
+    }
+}
+impl<'r> ::core::fmt::Display for ComponentDefinitionV2Reader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{} {{ ", Self::NAME)?;
+        write!(f, "{}: {}", "component_name", self.component_name())?;
+        write!(f, ", {}: {}", "info_hash", self.info_hash())?;
+        write!(f, ", {}: {}", "value_schema_hash", self.value_schema_hash())?;
+        write!(f, ", {}: {}", "version", self.version())?;
+        write!(f, ", {}: {}", "delegates", self.delegates())?;
+        write!(f, ", {}: {}", "flags", self.flags())?;
+        write!(f, ", {}: {}", "systems", self.systems())?;
+        write!(f, ", {}: {}", "authority", self.authority())?;
+        write!(f, ", {}: {}", "entity_type", self.entity_type())?;
+        let extra_count = self.count_extra_fields();
+        if extra_count != 0 {
+            write!(f, ", .. ({} fields)", extra_count)?;
+        }
+        write!(f, " }}")
+    }
+}
+impl<'r> ComponentDefinitionV2Reader<'r> {
+    pub const FIELD_COUNT: usize = 9;
+    pub fn total_size(&self) -> usize {
+        molecule::unpack_number(self.as_slice()) as usize
+    }
+    pub fn field_count(&self) -> usize {
+        if self.total_size() == molecule::NUMBER_SIZE {
+            0
+        } else {
+            (molecule::unpack_number(&self.as_slice()[molecule::NUMBER_SIZE..]) as usize / 4) - 1
+        }
+    }
+    pub fn count_extra_fields(&self) -> usize {
+        self.field_count() - Self::FIELD_COUNT
+    }
+    pub fn has_extra_fields(&self) -> bool {
+        Self::FIELD_COUNT != self.field_count()
+    }
+    pub fn component_name(&self) -> StringReader<'r> {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[4..]) as usize;
+        let end = molecule::unpack_number(&slice[8..]) as usize;
+        StringReader::new_unchecked(&self.as_slice()[start..end])
+    }
+    pub fn info_hash(&self) -> Byte32Reader<'r> {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[8..]) as usize;
+        let end = molecule::unpack_number(&slice[12..]) as usize;
+        Byte32Reader::new_unchecked(&self.as_slice()[start..end])
+    }
+    pub fn value_schema_hash(&self) -> Byte32Reader<'r> {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[12..]) as usize;
+        let end = molecule::unpack_number(&slice[16..]) as usize;
+        Byte32Reader::new_unchecked(&self.as_slice()[start..end])
+    }
+    pub fn version(&self) -> SemVerReader<'r> {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[16..]) as usize;
+        let end = molecule::unpack_number(&slice[20..]) as usize;
+        SemVerReader::new_unchecked(&self.as_slice()[start..end])
+    }
+    pub fn delegates(&self) -> ScriptVecReader<'r> {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[20..]) as usize;
+        let end = molecule::unpack_number(&slice[24..]) as usize;
+        ScriptVecReader::new_unchecked(&self.as_slice()[start..end])
+    }
+    pub fn flags(&self) -> Uint32Reader<'r> {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[24..]) as usize;
+        let end = molecule::unpack_number(&slice[28..]) as usize;
+        Uint32Reader::new_unchecked(&self.as_slice()[start..end])
+    }
+    pub fn systems(&self) -> Byte32VecReader<'r> {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[28..]) as usize;
+        let end = molecule::unpack_number(&slice[32..]) as usize;
+        Byte32VecReader::new_unchecked(&self.as_slice()[start..end])
+    }
+    pub fn authority(&self) -> Byte32OptReader<'r> {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[32..]) as usize;
+        let end = molecule::unpack_number(&slice[36..]) as usize;
+        Byte32OptReader::new_unchecked(&self.as_slice()[start..end])
+    }
+    pub fn entity_type(&self) -> EntityTypeOptReader<'r> {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[36..]) as usize;
+        if self.has_extra_fields() {
+            let end = molecule::unpack_number(&slice[40..]) as usize;
+            EntityTypeOptReader::new_unchecked(&self.as_slice()[start..end])
+        } else {
+            EntityTypeOptReader::new_unchecked(&self.as_slice()[start..])
+        }
+    }
+}
+impl<'r> molecule::prelude::Reader<'r> for ComponentDefinitionV2Reader<'r> {
+    type Entity = ComponentDefinitionV2;
+    const NAME: &'static str = "ComponentDefinitionV2Reader";
+    fn to_entity(&self) -> Self::Entity {
+        Self::Entity::new_unchecked(self.as_slice().to_owned().into())
+    }
+    fn new_unchecked(slice: &'r [u8]) -> Self {
+        ComponentDefinitionV2Reader(slice)
+    }
+    fn as_slice(&self) -> &'r [u8] {
+        self.0
+    }
+    fn verify(slice: &[u8], compatible: bool) -> molecule::error::VerificationResult<()> {
+        use molecule::verification_error as ve;
+        let slice_len = slice.len();
+        if slice_len < molecule::NUMBER_SIZE {
+            return ve!(Self, HeaderIsBroken, molecule::NUMBER_SIZE, slice_len);
+        }
+        let total_size = molecule::unpack_number(slice) as usize;
+        if slice_len != total_size {
+            return ve!(Self, TotalSizeNotMatch, total_size, slice_len);
+        }
+        if slice_len < molecule::NUMBER_SIZE * 2 {
+            return ve!(Self, HeaderIsBroken, molecule::NUMBER_SIZE * 2, slice_len);
+        }
+        let offset_first = molecule::unpack_number(&slice[molecule::NUMBER_SIZE..]) as usize;
+        if offset_first % molecule::NUMBER_SIZE != 0 || offset_first < molecule::NUMBER_SIZE * 2 {
+            return ve!(Self, OffsetsNotMatch);
+        }
+        if slice_len < offset_first {
+            return ve!(Self, HeaderIsBroken, offset_first, slice_len);
+        }
+        let field_count = offset_first / molecule::NUMBER_SIZE - 1;
+        if field_count < Self::FIELD_COUNT {
+            return ve!(Self, FieldCountNotMatch, Self::FIELD_COUNT, field_count);
+        } else if !compatible && field_count > Self::FIELD_COUNT {
+            return ve!(Self, FieldCountNotMatch, Self::FIELD_COUNT, field_count);
+        };
+        let mut offsets: Vec<usize> = slice[molecule::NUMBER_SIZE..offset_first]
+            .chunks_exact(molecule::NUMBER_SIZE)
+            .map(|x| molecule::unpack_number(x) as usize)
+            .collect();
+        offsets.push(total_size);
+        if offsets.windows(2).any(|i| i[0] > i[1]) {
+            return ve!(Self, OffsetsNotMatch);
+        }
+        StringReader::verify(&slice[offsets[0]..offsets[1]], compatible)?;
+        Byte32Reader::verify(&slice[offsets[1]..offsets[2]], compatible)?;
+        Byte32Reader::verify(&slice[offsets[2]..offsets[3]], compatible)?;
+        SemVerReader::verify(&slice[offsets[3]..offsets[4]], compatible)?;
+        ScriptVecReader::verify(&slice[offsets[4]..offsets[5]], compatible)?;
+        Uint32Reader::verify(&slice[offsets[5]..offsets[6]], compatible)?;
+        Byte32VecReader::verify(&slice[offsets[6]..offsets[7]], compatible)?;
+        Byte32OptReader::verify(&slice[offsets[7]..offsets[8]], compatible)?;
+        EntityTypeOptReader::verify(&slice[offsets[8]..offsets[9]], compatible)?;
+        Ok(())
+    }
+}
+#[derive(Debug, Default)]
+pub struct ComponentDefinitionV2Builder {
+    pub(crate) component_name: String,
+    pub(crate) info_hash: Byte32,
+    pub(crate) value_schema_hash: Byte32,
+    pub(crate) version: SemVer,
+    pub(crate) delegates: ScriptVec,
+    pub(crate) flags: Uint32,
+    pub(crate) systems: Byte32Vec,
+    pub(crate) authority: Byte32Opt,
+    pub(crate) entity_type: EntityTypeOpt,
+}
+impl ComponentDefinitionV2Builder {
+    pub const FIELD_COUNT: usize = 9;
+    pub fn component_name(mut self, v: String) -> Self {
+        self.component_name = v;
+        self
+    }
+    pub fn info_hash(mut self, v: Byte32) -> Self {
+        self.info_hash = v;
+        self
+    }
+    pub fn value_schema_hash(mut self, v: Byte32) -> Self {
+        self.value_schema_hash = v;
+        self
+    }
+    pub fn version(mut self, v: SemVer) -> Self {
+        self.version = v;
+        self
+    }
+    pub fn delegates(mut self, v: ScriptVec) -> Self {
+        self.delegates = v;
+        self
+    }
+    pub fn flags(mut self, v: Uint32) -> Self {
+        self.flags = v;
+        self
+    }
+    pub fn systems(mut self, v: Byte32Vec) -> Self {
+        self.systems = v;
+        self
+    }
+    pub fn authority(mut self, v: Byte32Opt) -> Self {
+        self.authority = v;
+        self
+    }
+    pub fn entity_type(mut self, v: EntityTypeOpt) -> Self {
+        self.entity_type = v;
+        self
+    }
+}
+impl molecule::prelude::Builder for ComponentDefinitionV2Builder {
+    type Entity = ComponentDefinitionV2;
+    const NAME: &'static str = "ComponentDefinitionV2Builder";
+    fn expected_length(&self) -> usize {
+        molecule::NUMBER_SIZE * (Self::FIELD_COUNT + 1)
+            + self.component_name.as_slice().len()
+            + self.info_hash.as_slice().len()
+            + self.value_schema_hash.as_slice().len()
+            + self.version.as_slice().len()
+            + self.delegates.as_slice().len()
+            + self.flags.as_slice().len()
+            + self.systems.as_slice().len()
+            + self.authority.as_slice().len()
+            + self.entity_type.as_slice().len()
+    }
+    fn write<W: molecule::io::Write>(&self, writer: &mut W) -> molecule::io::Result<()> {
+        let mut total_size = molecule::NUMBER_SIZE * (Self::FIELD_COUNT + 1);
+        let mut offsets = Vec::with_capacity(Self::FIELD_COUNT);
+        offsets.push(total_size);
+        total_size += self.component_name.as_slice().len();
+        offsets.push(total_size);
+        total_size += self.info_hash.as_slice().len();
+        offsets.push(total_size);
+        total_size += self.value_schema_hash.as_slice().len();
+        offsets.push(total_size);
+        total_size += self.version.as_slice().len();
+        offsets.push(total_size);
+        total_size += self.delegates.as_slice().len();
+        offsets.push(total_size);
+        total_size += self.flags.as_slice().len();
+        offsets.push(total_size);
+        total_size += self.systems.as_slice().len();
+        offsets.push(total_size);
+        total_size += self.authority.as_slice().len();
+        offsets.push(total_size);
+        total_size += self.entity_type.as_slice().len();
+        writer.write_all(&molecule::pack_number(total_size as molecule::Number))?;
+        for offset in offsets.into_iter() {
+            writer.write_all(&molecule::pack_number(offset as molecule::Number))?;
+        }
+        writer.write_all(self.component_name.as_slice())?;
+        writer.write_all(self.info_hash.as_slice())?;
+        writer.write_all(self.value_schema_hash.as_slice())?;
+        writer.write_all(self.version.as_slice())?;
+        writer.write_all(self.delegates.as_slice())?;
+        writer.write_all(self.flags.as_slice())?;
+        writer.write_all(self.systems.as_slice())?;
+        writer.write_all(self.authority.as_slice())?;
+        writer.write_all(self.entity_type.as_slice())?;
+        Ok(())
+    }
+    fn build(&self) -> Self::Entity {
+        let mut inner = Vec::with_capacity(self.expected_length());
+        self.write(&mut inner)
+            .unwrap_or_else(|_| panic!("{} build should be ok", Self::NAME));
+        ComponentDefinitionV2::new_unchecked(inner.into())
+    }
+}
+#[derive(Clone)]
 pub struct ComponentDefinition(molecule::bytes::Bytes);
 impl ::core::fmt::LowerHex for ComponentDefinition {
     fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
@@ -571,7 +1192,7 @@ impl ComponentDefinition {
         0, 16, 0, 0, 0, 48, 0, 0, 0, 49, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
         0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
     ];
-    pub const ITEMS_COUNT: usize = 1;
+    pub const ITEMS_COUNT: usize = 2;
     pub fn item_id(&self) -> molecule::Number {
         molecule::unpack_number(self.as_slice())
     }
@@ -579,6 +1200,7 @@ impl ComponentDefinition {
         let inner = self.0.slice(molecule::NUMBER_SIZE..);
         match self.item_id() {
             0 => ComponentDefinitionV1::new_unchecked(inner).into(),
+            1 => ComponentDefinitionV2::new_unchecked(inner).into(),
             _ => panic!("{}: invalid data", Self::NAME),
         }
     }
@@ -635,7 +1257,7 @@ impl<'r> ::core::fmt::Display for ComponentDefinitionReader<'r> {
     }
 }
 impl<'r> ComponentDefinitionReader<'r> {
-    pub const ITEMS_COUNT: usize = 1;
+    pub const ITEMS_COUNT: usize = 2;
     pub fn item_id(&self) -> molecule::Number {
         molecule::unpack_number(self.as_slice())
     }
@@ -643,6 +1265,7 @@ impl<'r> ComponentDefinitionReader<'r> {
         let inner = &self.as_slice()[molecule::NUMBER_SIZE..];
         match self.item_id() {
             0 => ComponentDefinitionV1Reader::new_unchecked(inner).into(),
+            1 => ComponentDefinitionV2Reader::new_unchecked(inner).into(),
             _ => panic!("{}: invalid data", Self::NAME),
         }
     }
@@ -669,6 +1292,7 @@ impl<'r> molecule::prelude::Reader<'r> for ComponentDefinitionReader<'r> {
         let inner_slice = &slice[molecule::NUMBER_SIZE..];
         match item_id {
             0 => ComponentDefinitionV1Reader::verify(inner_slice, compatible),
+            1 => ComponentDefinitionV2Reader::verify(inner_slice, compatible),
             _ => ve!(Self, UnknownItem, Self::ITEMS_COUNT, item_id),
         }?;
         Ok(())
@@ -677,7 +1301,7 @@ impl<'r> molecule::prelude::Reader<'r> for ComponentDefinitionReader<'r> {
 #[derive(Debug, Default)]
 pub struct ComponentDefinitionBuilder(pub(crate) ComponentDefinitionUnion);
 impl ComponentDefinitionBuilder {
-    pub const ITEMS_COUNT: usize = 1;
+    pub const ITEMS_COUNT: usize = 2;
     pub fn set<I>(mut self, v: I) -> Self
     where
         I: ::core::convert::Into<ComponentDefinitionUnion>,
@@ -706,10 +1330,12 @@ impl molecule::prelude::Builder for ComponentDefinitionBuilder {
 #[derive(Debug, Clone)]
 pub enum ComponentDefinitionUnion {
     ComponentDefinitionV1(ComponentDefinitionV1),
+    ComponentDefinitionV2(ComponentDefinitionV2),
 }
 #[derive(Debug, Clone, Copy)]
 pub enum ComponentDefinitionUnionReader<'r> {
     ComponentDefinitionV1(ComponentDefinitionV1Reader<'r>),
+    ComponentDefinitionV2(ComponentDefinitionV2Reader<'r>),
 }
 impl ::core::default::Default for ComponentDefinitionUnion {
     fn default() -> Self {
@@ -728,6 +1354,15 @@ impl ::core::fmt::Display for ComponentDefinitionUnion {
                     item
                 )
             }
+            ComponentDefinitionUnion::ComponentDefinitionV2(ref item) => {
+                write!(
+                    f,
+                    "{}::{}({})",
+                    Self::NAME,
+                    ComponentDefinitionV2::NAME,
+                    item
+                )
+            }
         }
     }
 }
@@ -743,6 +1378,15 @@ impl<'r> ::core::fmt::Display for ComponentDefinitionUnionReader<'r> {
                     item
                 )
             }
+            ComponentDefinitionUnionReader::ComponentDefinitionV2(ref item) => {
+                write!(
+                    f,
+                    "{}::{}({})",
+                    Self::NAME,
+                    ComponentDefinitionV2::NAME,
+                    item
+                )
+            }
         }
     }
 }
@@ -750,6 +1394,7 @@ impl ComponentDefinitionUnion {
     pub(crate) fn display_inner(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
         match self {
             ComponentDefinitionUnion::ComponentDefinitionV1(ref item) => write!(f, "{}", item),
+            ComponentDefinitionUnion::ComponentDefinitionV2(ref item) => write!(f, "{}", item),
         }
     }
 }
@@ -759,6 +1404,9 @@ impl<'r> ComponentDefinitionUnionReader<'r> {
             ComponentDefinitionUnionReader::ComponentDefinitionV1(ref item) => {
                 write!(f, "{}", item)
             }
+            ComponentDefinitionUnionReader::ComponentDefinitionV2(ref item) => {
+                write!(f, "{}", item)
+            }
         }
     }
 }
@@ -774,31 +1422,48 @@ impl<'r> ::core::convert::From<ComponentDefinitionV1Reader<'r>>
         ComponentDefinitionUnionReader::ComponentDefinitionV1(item)
     }
 }
+impl ::core::convert::From<ComponentDefinitionV2> for ComponentDefinitionUnion {
+    fn from(item: ComponentDefinitionV2) -> Self {
+        ComponentDefinitionUnion::ComponentDefinitionV2(item)
+    }
+}
+impl<'r> ::core::convert::From<ComponentDefinitionV2Reader<'r>>
+    for ComponentDefinitionUnionReader<'r>
+{
+    fn from(item: ComponentDefinitionV2Reader<'r>) -> Self {
+        ComponentDefinitionUnionReader::ComponentDefinitionV2(item)
+    }
+}
 impl ComponentDefinitionUnion {
     pub const NAME: &'static str = "ComponentDefinitionUnion";
     pub fn as_bytes(&self) -> molecule::bytes::Bytes {
         match self {
             ComponentDefinitionUnion::ComponentDefinitionV1(item) => item.as_bytes(),
+            ComponentDefinitionUnion::ComponentDefinitionV2(item) => item.as_bytes(),
         }
     }
     pub fn as_slice(&self) -> &[u8] {
         match self {
             ComponentDefinitionUnion::ComponentDefinitionV1(item) => item.as_slice(),
+            ComponentDefinitionUnion::ComponentDefinitionV2(item) => item.as_slice(),
         }
     }
     pub fn item_id(&self) -> molecule::Number {
         match self {
             ComponentDefinitionUnion::ComponentDefinitionV1(_) => 0,
+            ComponentDefinitionUnion::ComponentDefinitionV2(_) => 1,
         }
     }
     pub fn item_name(&self) -> &str {
         match self {
             ComponentDefinitionUnion::ComponentDefinitionV1(_) => "ComponentDefinitionV1",
+            ComponentDefinitionUnion::ComponentDefinitionV2(_) => "ComponentDefinitionV2",
         }
     }
     pub fn as_reader<'r>(&'r self) -> ComponentDefinitionUnionReader<'r> {
         match self {
             ComponentDefinitionUnion::ComponentDefinitionV1(item) => item.as_reader().into(),
+            ComponentDefinitionUnion::ComponentDefinitionV2(item) => item.as_reader().into(),
         }
     }
 }
@@ -807,16 +1472,19 @@ impl<'r> ComponentDefinitionUnionReader<'r> {
     pub fn as_slice(&self) -> &'r [u8] {
         match self {
             ComponentDefinitionUnionReader::ComponentDefinitionV1(item) => item.as_slice(),
+            ComponentDefinitionUnionReader::ComponentDefinitionV2(item) => item.as_slice(),
         }
     }
     pub fn item_id(&self) -> molecule::Number {
         match self {
             ComponentDefinitionUnionReader::ComponentDefinitionV1(_) => 0,
+            ComponentDefinitionUnionReader::ComponentDefinitionV2(_) => 1,
         }
     }
     pub fn item_name(&self) -> &str {
         match self {
             ComponentDefinitionUnionReader::ComponentDefinitionV1(_) => "ComponentDefinitionV1",
+            ComponentDefinitionUnionReader::ComponentDefinitionV2(_) => "ComponentDefinitionV2",
         }
     }
 }
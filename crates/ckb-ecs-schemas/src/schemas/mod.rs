@@ -1,6 +1,11 @@
 mod blockchain;
 mod component;
 mod component_definition;
+mod dapp_info;
+mod entity;
+mod system;
+mod typed_message;
+mod value_schema;
 
 #[allow(clippy::all)]
 pub use blockchain::*;
@@ -8,6 +13,16 @@ pub use blockchain::*;
 pub use component::*;
 #[allow(clippy::all)]
 pub use component_definition::*;
+#[allow(clippy::all)]
+pub use dapp_info::*;
+#[allow(clippy::all)]
+pub use entity::*;
+#[allow(clippy::all)]
+pub use system::*;
+#[allow(clippy::all)]
+pub use typed_message::*;
+#[allow(clippy::all)]
+pub use value_schema::*;
 
 #[test]
 fn build_component_definition() {
@@ -37,3 +52,33 @@ fn build_component_definition() {
         }
     }
 }
+
+#[test]
+fn read_component_definition_v2() {
+    use molecule::prelude::*;
+
+    let version = SemVerBuilder::default()
+        .major(1u32.into())
+        .minor(2u32.into())
+        .patch(3u32.into())
+        .build();
+    let definition_v2 = ComponentDefinitionV2Builder::default()
+        .component_name("test".into())
+        .info_hash([42u8; 32].into())
+        .version(version)
+        .flags(1u32.into())
+        .build();
+    let definition = ComponentDefinitionBuilder::default()
+        .set(definition_v2)
+        .build();
+
+    let reader = ComponentDefinitionReader::from_slice(definition.as_slice())
+        .ok()
+        .expect("decode ComponentDefinition");
+    assert_eq!(reader.component_name().raw_data(), "test".as_bytes());
+    assert_eq!(reader.version(), (1, 2, 3));
+    assert_eq!(reader.flags(), 1);
+    assert_eq!(reader.delegates_len(), 0);
+    assert!(reader.value_schema_hash().is_none());
+    assert!(reader.authority().is_none());
+}
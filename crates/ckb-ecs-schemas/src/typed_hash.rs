@@ -0,0 +1,148 @@
+//! EIP-712 style structured hashing, so wallets can show users what they sign.
+//!
+//! All hashes are ckb blake2b-256 (personalization `ckb-default-hash`).
+//!
+//! - The type hash of a struct is the hash of its type string, such as
+//!   `TypedMessage(String action,Byte32 target,Bytes payload,Uint64 nonce)`.
+//! - The struct hash is the hash of the type hash followed by the encoded fields in order.
+//!   Fixed sized fields (arrays and structs) are encoded as their molecule bytes, and dynamic
+//!   sized fields (vectors, tables, options and unions) are encoded as the hash of their molecule
+//!   raw data.
+//! - The domain separator is the struct hash of `CkbEcsDomain`, which binds the message to a
+//!   DappInfo, a component definition and the nonce cell of the lock.
+//! - The digest to sign is `hash(0x19 0x01 || domain_separator || struct_hash)`.
+use crate::schemas::TypedMessageReader;
+use blake2b_rs::{Blake2b, Blake2bBuilder};
+use molecule::prelude::*;
+
+pub const CKB_PERSONALIZATION: &[u8] = b"ckb-default-hash";
+
+pub const DOMAIN_TYPE: &str =
+    "CkbEcsDomain(Byte32 info_hash,Byte32 definition_hash,Byte32 nonce_type_hash)";
+pub const TYPED_MESSAGE_TYPE: &str =
+    "TypedMessage(String action,Byte32 target,Bytes payload,Uint64 nonce)";
+
+const DIGEST_PREFIX: [u8; 2] = [0x19, 0x01];
+
+pub fn new_blake2b() -> Blake2b {
+    Blake2bBuilder::new(32)
+        .personal(CKB_PERSONALIZATION)
+        .build()
+}
+
+pub fn hash(data: &[u8]) -> [u8; 32] {
+    let mut blake2b = new_blake2b();
+    blake2b.update(data);
+    let mut ret = [0u8; 32];
+    blake2b.finalize(&mut ret);
+    ret
+}
+
+/// Encodes the fields of a struct in order.
+pub struct StructHasher(Blake2b);
+
+impl StructHasher {
+    pub fn new(type_string: &str) -> Self {
+        let mut blake2b = new_blake2b();
+        blake2b.update(&hash(type_string.as_bytes()));
+        Self(blake2b)
+    }
+
+    /// Fixed sized fields are encoded as is.
+    pub fn fixed(mut self, bytes: &[u8]) -> Self {
+        self.0.update(bytes);
+        self
+    }
+
+    /// Dynamic sized fields are encoded as the hash of the raw data.
+    pub fn dynamic(mut self, raw_data: &[u8]) -> Self {
+        self.0.update(&hash(raw_data));
+        self
+    }
+
+    pub fn finish(self) -> [u8; 32] {
+        let mut ret = [0u8; 32];
+        self.0.finalize(&mut ret);
+        ret
+    }
+}
+
+/// `info_hash` is the DappInfo reference in the definition, `definition_hash` is the code hash in
+/// the component-type args, and `nonce_type_hash` is the nonce in the typed-message-lock args.
+pub fn domain_separator(
+    info_hash: &[u8; 32],
+    definition_hash: &[u8; 32],
+    nonce_type_hash: &[u8; 32],
+) -> [u8; 32] {
+    StructHasher::new(DOMAIN_TYPE)
+        .fixed(info_hash)
+        .fixed(definition_hash)
+        .fixed(nonce_type_hash)
+        .finish()
+}
+
+pub fn digest(domain_separator: &[u8; 32], struct_hash: &[u8; 32]) -> [u8; 32] {
+    let mut blake2b = new_blake2b();
+    blake2b.update(&DIGEST_PREFIX);
+    blake2b.update(domain_separator);
+    blake2b.update(struct_hash);
+    let mut ret = [0u8; 32];
+    blake2b.finalize(&mut ret);
+    ret
+}
+
+impl<'r> TypedMessageReader<'r> {
+    pub fn struct_hash(&self) -> [u8; 32] {
+        StructHasher::new(TYPED_MESSAGE_TYPE)
+            .dynamic(self.action().raw_data())
+            .fixed(self.target().as_slice())
+            .dynamic(self.payload().raw_data())
+            .fixed(self.nonce().as_slice())
+            .finish()
+    }
+
+    pub fn digest(&self, domain_separator: &[u8; 32]) -> [u8; 32] {
+        digest(domain_separator, &self.struct_hash())
+    }
+}
+
+#[cfg(test)]
+fn decode_hex(hex: &str) -> [u8; 32] {
+    let mut ret = [0u8; 32];
+    for (i, byte) in ret.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).expect("hex");
+    }
+    ret
+}
+
+#[test]
+fn typed_message_digest_vectors() {
+    use crate::schemas::TypedMessageBuilder;
+
+    assert_eq!(
+        hash(b""),
+        decode_hex("44f4c69744d5f8c55d642062949dcae49bc4e7ef43d388c5a12f42b5633d163e")
+    );
+
+    let domain = domain_separator(&[1u8; 32], &[2u8; 32], &[4u8; 32]);
+    assert_eq!(
+        domain,
+        decode_hex("91c9428466ea65ef0f3af42a73444f47ce5a6523882dcd37eef2722961f41920")
+    );
+
+    let message = TypedMessageBuilder::default()
+        .action("move".into())
+        .target([3u8; 32].into())
+        .payload((&[1u8, 2, 3] as &[u8]).into())
+        .nonce(7u64.into())
+        .build();
+    let reader = message.as_reader();
+    assert_eq!(
+        reader.struct_hash(),
+        decode_hex("88aea9267172bbae9edcfcf00837fba819c99eb8211280b9f83496ba7fe13373")
+    );
+    assert_eq!(
+        reader.digest(&domain),
+        decode_hex("47639174a7c2895f3918e8a9ee6d122f3b619f506864584af7736106d23b2166")
+    );
+}
@@ -13,20 +13,30 @@ pub struct Setup {
     pub always_success_out_point: packed::OutPoint,
 
     pub always_success_script: packed::Script,
+    pub authority_lock_script: packed::Script,
+
+    pub dapp_info_cell_dep: packed::CellDep,
+    pub dapp_info_hash: [u8; 32],
+    // The DappInfo type script stands in for dapp-info-type.
+    pub dapp_info_type_script: packed::Script,
+}
+
+fn data(info_hash: [u8; 32]) -> Bytes {
+    definition("test", info_hash, &[])
 }
 
-fn data() -> Bytes {
+fn definition(component_name: &str, info_hash: [u8; 32], delegate_args: &[u8]) -> Bytes {
     use ckb_ecs_schemas::*;
     use molecule::prelude::*;
 
     let script = ScriptBuilder::default()
         .code_hash([1u8; 32].into())
         .hash_type(1.into())
-        .args((&[] as &[u8]).into())
+        .args(delegate_args.into())
         .build();
     let definition_v1 = ComponentDefinitionV1Builder::default()
-        .component_name("test".into())
-        .info_hash([42u8; 32].into())
+        .component_name(component_name.into())
+        .info_hash(info_hash.into())
         .delegate(script)
         .build();
     ComponentDefinitionBuilder::default()
@@ -35,6 +45,36 @@ fn data() -> Bytes {
         .as_bytes()
 }
 
+fn definition_v2(
+    component_name: &str,
+    info_hash: [u8; 32],
+    value_schema_hash: [u8; 32],
+    version: (u32, u32, u32),
+    authority: Option<&packed::Script>,
+) -> Bytes {
+    use ckb_ecs_schemas::*;
+    use molecule::prelude::*;
+
+    let version = SemVerBuilder::default()
+        .major(version.0.into())
+        .minor(version.1.into())
+        .patch(version.2.into())
+        .build();
+    let authority = authority
+        .map(|lock| -> [u8; 32] { ckb_hash(lock.as_slice()).try_into().expect("lock hash") });
+    let definition_v2 = ComponentDefinitionV2Builder::default()
+        .component_name(component_name.into())
+        .info_hash(info_hash.into())
+        .value_schema_hash(value_schema_hash.into())
+        .version(version)
+        .authority(authority.into())
+        .build();
+    ComponentDefinitionBuilder::default()
+        .set(definition_v2)
+        .build()
+        .as_bytes()
+}
+
 impl Setup {
     fn new() -> Self {
         let mut context = Context::default();
@@ -47,6 +87,30 @@ impl Setup {
         let always_success_script = context
             .build_script(&always_success_out_point, Bytes::new())
             .expect("script");
+        let authority_lock_script = context
+            .build_script(&always_success_out_point, Bytes::from(vec![42]))
+            .expect("script");
+
+        let dapp_info = super::dapp_info_type_tests::dapp_info(
+            "dapp",
+            1,
+            &["test", "other"],
+            &always_success_script,
+        );
+        let dapp_info_hash = ckb_hash(&dapp_info).try_into().expect("data hash");
+        let dapp_info_out_point = context.create_cell(
+            packed::CellOutput::new_builder()
+                .capacity(2000.pack())
+                .lock(always_success_script.clone())
+                .build(),
+            dapp_info,
+        );
+        let dapp_info_cell_dep = packed::CellDep::new_builder()
+            .out_point(dapp_info_out_point)
+            .build();
+        let dapp_info_type_script = context
+            .build_script(&always_success_out_point, Bytes::from(vec![8u8; 32]))
+            .expect("script");
 
         Self {
             context,
@@ -54,14 +118,44 @@ impl Setup {
             component_definition_type_out_point,
             always_success_out_point,
             always_success_script,
+            authority_lock_script,
+
+            dapp_info_cell_dep,
+            dapp_info_hash,
+            dapp_info_type_script,
         }
     }
 
+    fn dapp_info_type_hash(&self) -> [u8; 32] {
+        ckb_hash(self.dapp_info_type_script.as_slice())
+            .try_into()
+            .expect("type hash")
+    }
+
+    // Returns the cell dep of the DappInfo guarded by the DappInfo type script.
+    fn typed_dapp_info_cell_dep(&mut self, version: u32) -> packed::CellDep {
+        let dapp_info = super::dapp_info_type_tests::dapp_info(
+            "dapp",
+            version,
+            &["test", "other"],
+            &self.always_success_script,
+        );
+        let out_point = self.context.create_cell(
+            packed::CellOutput::new_builder()
+                .capacity(2000.pack())
+                .lock(self.always_success_script.clone())
+                .type_(Some(self.dapp_info_type_script.clone()).pack())
+                .build(),
+            dapp_info,
+        );
+        packed::CellDep::new_builder().out_point(out_point).build()
+    }
+
     fn c(&mut self) -> &mut Context {
         return &mut self.context;
     }
 
-    fn input(&mut self, id_opt: Option<Bytes>) -> packed::CellInput {
+    fn input(&mut self, id_opt: Option<Bytes>, data: Bytes) -> packed::CellInput {
         let type_opt = id_opt.map(|id| {
             self.context
                 .build_script(&self.component_definition_type_out_point, id)
@@ -74,6 +168,19 @@ impl Setup {
                 .lock(self.always_success_script.clone())
                 .type_(type_opt.pack())
                 .build(),
+            data,
+        );
+        packed::CellInput::new_builder()
+            .previous_output(out_point)
+            .build()
+    }
+
+    fn authority_input(&mut self) -> packed::CellInput {
+        let out_point = self.context.create_cell(
+            packed::CellOutput::new_builder()
+                .capacity(2000.pack())
+                .lock(self.authority_lock_script.clone())
+                .build(),
             Bytes::new(),
         );
         packed::CellInput::new_builder()
@@ -105,9 +212,10 @@ fn test_update_type_id() {
     let dummy_id = Bytes::from(vec![1u8; 32]);
 
     let tx = TransactionBuilder::default()
-        .input(env.input(Some(dummy_id.clone())))
+        .input(env.input(Some(dummy_id.clone()), data(env.dapp_info_hash)))
         .output(env.output(Some(dummy_id.clone())))
-        .output_data(data().pack())
+        .output_data(data(env.dapp_info_hash).pack())
+        .cell_dep(env.dapp_info_cell_dep.clone())
         .build();
     assert_tx_ok(env.c(), tx, "update cell with type_id");
 }
@@ -120,9 +228,9 @@ fn test_delete_type_id() {
 
     // delete
     let tx = TransactionBuilder::default()
-        .input(env.input(Some(dummy_id.clone())))
+        .input(env.input(Some(dummy_id.clone()), data(env.dapp_info_hash)))
         .output(env.output(None))
-        .output_data(data().pack())
+        .output_data(data(env.dapp_info_hash).pack())
         .build();
     assert_tx_ok(env.c(), tx, "delete cell with type_id");
 }
@@ -132,12 +240,13 @@ fn test_create_type_id() {
     let mut env = Setup::new();
 
     // create
-    let input = env.input(None);
+    let input = env.input(None, Bytes::new());
     let new_id = new_type_id(&input, 0);
     let tx = TransactionBuilder::default()
         .input(input)
         .output(env.output(Some(new_id)))
-        .output_data(data().pack())
+        .output_data(data(env.dapp_info_hash).pack())
+        .cell_dep(env.dapp_info_cell_dep.clone())
         .build();
     assert_tx_ok(env.c(), tx, "create cell with type_id");
 }
@@ -147,9 +256,9 @@ fn test_invalid_data() {
     let mut env = Setup::new();
 
     // create
-    let input = env.input(None);
+    let input = env.input(None, Bytes::new());
     let new_id = new_type_id(&input, 0);
-    let mut data = data().to_vec();
+    let mut data = data(env.dapp_info_hash).to_vec();
     data[2] = !data[2];
     let tx = TransactionBuilder::default()
         .input(input)
@@ -164,12 +273,322 @@ fn test_invalid_data() {
     );
 }
 
-fn new_type_id(input: &packed::CellInput, output_index: u64) -> Bytes {
-    let mut blake2b = new_blake2b();
+#[test]
+fn test_dapp_info_not_found() {
+    let mut env = Setup::new();
+
+    let input = env.input(None, Bytes::new());
+    let new_id = new_type_id(&input, 0);
+    let tx = TransactionBuilder::default()
+        .input(input)
+        .output(env.output(Some(new_id)))
+        .output_data(data(env.dapp_info_hash).pack())
+        .build();
+    assert_tx_err_code(
+        env.c(),
+        tx,
+        "create cell without dapp info",
+        Error::DappInfoNotFound as i8,
+    );
+}
+
+#[test]
+fn test_dapp_info_by_type_hash() {
+    let mut env = Setup::new();
+
+    // The definition was created with DappInfo version 1, and DappInfo has been updated since.
+    let id = Bytes::from(vec![1u8; 32]);
+    let authority = env.authority_lock_script.clone();
+    let info_hash = env.dapp_info_type_hash();
+    let old_data = definition_v2("test", info_hash, [0u8; 32], (1, 0, 0), Some(&authority));
+    let new_data = definition_v2("test", info_hash, [0u8; 32], (1, 1, 0), Some(&authority));
+    let dapp_info_cell_dep = env.typed_dapp_info_cell_dep(2);
+    let tx = TransactionBuilder::default()
+        .input(env.input(Some(id.clone()), old_data))
+        .input(env.authority_input())
+        .output(env.output(Some(id)))
+        .output_data(new_data.pack())
+        .cell_dep(dapp_info_cell_dep)
+        .build();
+    assert_tx_ok(
+        env.c(),
+        tx,
+        "update definition referencing DappInfo by type hash",
+    );
+}
+
+#[test]
+fn test_transfer_without_dapp_info() {
+    let mut env = Setup::new();
+
+    // The DappInfo with the data hash is no longer live, but the unchanged definition can still
+    // move to another cell.
+    let id = Bytes::from(vec![1u8; 32]);
+    let tx = TransactionBuilder::default()
+        .input(env.input(Some(id.clone()), data([3u8; 32])))
+        .output(env.output(Some(id)))
+        .output_data(data([3u8; 32]).pack())
+        .build();
+    assert_tx_ok(env.c(), tx, "transfer definition without DappInfo");
+}
+
+#[test]
+fn test_component_not_declared() {
+    let mut env = Setup::new();
+
+    let dapp_info =
+        super::dapp_info_type_tests::dapp_info("dapp", 1, &["other"], &env.always_success_script);
+    let dapp_info_hash = ckb_hash(&dapp_info).try_into().expect("data hash");
+    let dapp_info_out_point = env.context.create_cell(
+        packed::CellOutput::new_builder()
+            .capacity(2000.pack())
+            .lock(env.always_success_script.clone())
+            .build(),
+        dapp_info,
+    );
+
+    let input = env.input(None, Bytes::new());
+    let new_id = new_type_id(&input, 0);
+    let tx = TransactionBuilder::default()
+        .input(input)
+        .output(env.output(Some(new_id)))
+        .output_data(data(dapp_info_hash).pack())
+        .cell_dep(
+            packed::CellDep::new_builder()
+                .out_point(dapp_info_out_point)
+                .build(),
+        )
+        .build();
+    assert_tx_err_code(
+        env.c(),
+        tx,
+        "create cell with undeclared component",
+        Error::ComponentNotDeclared as i8,
+    );
+}
 
-    blake2b.update(input.as_slice());
-    blake2b.update(&output_index.to_le_bytes());
-    let mut ret = vec![0; 32];
-    blake2b.finalize(&mut ret);
-    Bytes::from(ret)
+#[test]
+fn test_update_by_authority() {
+    let mut env = Setup::new();
+
+    let id = Bytes::from(vec![1u8; 32]);
+    let authority = env.authority_lock_script.clone();
+    let old_data = definition_v2(
+        "test",
+        env.dapp_info_hash,
+        [0u8; 32],
+        (1, 0, 0),
+        Some(&authority),
+    );
+    let new_data = definition_v2(
+        "test",
+        env.dapp_info_hash,
+        [0u8; 32],
+        (1, 1, 0),
+        Some(&authority),
+    );
+    let tx = TransactionBuilder::default()
+        .input(env.input(Some(id.clone()), old_data))
+        .input(env.authority_input())
+        .output(env.output(Some(id)))
+        .output_data(new_data.pack())
+        .cell_dep(env.dapp_info_cell_dep.clone())
+        .build();
+    assert_tx_ok(env.c(), tx, "update definition by authority");
+}
+
+#[test]
+fn test_update_without_authority() {
+    let mut env = Setup::new();
+
+    let id = Bytes::from(vec![1u8; 32]);
+    let authority = env.authority_lock_script.clone();
+    let old_data = definition_v2(
+        "test",
+        env.dapp_info_hash,
+        [0u8; 32],
+        (1, 0, 0),
+        Some(&authority),
+    );
+    let new_data = definition_v2(
+        "test",
+        env.dapp_info_hash,
+        [0u8; 32],
+        (1, 1, 0),
+        Some(&authority),
+    );
+    let tx = TransactionBuilder::default()
+        .input(env.input(Some(id.clone()), old_data))
+        .output(env.output(Some(id)))
+        .output_data(new_data.pack())
+        .cell_dep(env.dapp_info_cell_dep.clone())
+        .build();
+    assert_tx_err_code(
+        env.c(),
+        tx,
+        "update definition without authority",
+        Error::Unauthorized as i8,
+    );
+}
+
+#[test]
+fn test_update_immutable_definition() {
+    let mut env = Setup::new();
+
+    let id = Bytes::from(vec![1u8; 32]);
+    let new_data = definition("test", env.dapp_info_hash, &[1]);
+    let tx = TransactionBuilder::default()
+        .input(env.input(Some(id.clone()), data(env.dapp_info_hash)))
+        .input(env.authority_input())
+        .output(env.output(Some(id)))
+        .output_data(new_data.pack())
+        .cell_dep(env.dapp_info_cell_dep.clone())
+        .build();
+    assert_tx_err_code(
+        env.c(),
+        tx,
+        "update definition without authority",
+        Error::Unauthorized as i8,
+    );
+}
+
+#[test]
+fn test_rename_component() {
+    let mut env = Setup::new();
+
+    let id = Bytes::from(vec![1u8; 32]);
+    let authority = env.authority_lock_script.clone();
+    let old_data = definition_v2(
+        "test",
+        env.dapp_info_hash,
+        [0u8; 32],
+        (1, 0, 0),
+        Some(&authority),
+    );
+    let new_data = definition_v2(
+        "other",
+        env.dapp_info_hash,
+        [0u8; 32],
+        (1, 0, 0),
+        Some(&authority),
+    );
+    let tx = TransactionBuilder::default()
+        .input(env.input(Some(id.clone()), old_data))
+        .input(env.authority_input())
+        .output(env.output(Some(id)))
+        .output_data(new_data.pack())
+        .cell_dep(env.dapp_info_cell_dep.clone())
+        .build();
+    assert_tx_err_code(
+        env.c(),
+        tx,
+        "rename component",
+        Error::ImmutableFieldChanged as i8,
+    );
+}
+
+#[test]
+fn test_create_v2() {
+    let mut env = Setup::new();
+
+    // The components in the test DappInfo have empty schemas.
+    let value_schema_hash = ckb_hash(&[]).try_into().expect("schema hash");
+    let input = env.input(None, Bytes::new());
+    let new_id = new_type_id(&input, 0);
+    let tx = TransactionBuilder::default()
+        .input(input)
+        .output(env.output(Some(new_id)))
+        .output_data(
+            definition_v2(
+                "test",
+                env.dapp_info_hash,
+                value_schema_hash,
+                (1, 0, 0),
+                None,
+            )
+            .pack(),
+        )
+        .cell_dep(env.dapp_info_cell_dep.clone())
+        .build();
+    assert_tx_ok(env.c(), tx, "create v2");
+}
+
+#[test]
+fn test_value_schema_mismatch() {
+    let mut env = Setup::new();
+
+    let input = env.input(None, Bytes::new());
+    let new_id = new_type_id(&input, 0);
+    let tx = TransactionBuilder::default()
+        .input(input)
+        .output(env.output(Some(new_id)))
+        .output_data(definition_v2("test", env.dapp_info_hash, [1u8; 32], (1, 0, 0), None).pack())
+        .cell_dep(env.dapp_info_cell_dep.clone())
+        .build();
+    assert_tx_err_code(
+        env.c(),
+        tx,
+        "value schema mismatch",
+        Error::ValueSchemaMismatch as i8,
+    );
+}
+
+// V1 has no authority, so it cannot be upgraded.
+#[test]
+fn test_upgrade_v1_to_v2() {
+    let mut env = Setup::new();
+
+    let id = Bytes::from(vec![1u8; 32]);
+    let authority = env.authority_lock_script.clone();
+    let old_data = data(env.dapp_info_hash);
+    let new_data = definition_v2(
+        "test",
+        env.dapp_info_hash,
+        [0u8; 32],
+        (1, 0, 0),
+        Some(&authority),
+    );
+    let tx = TransactionBuilder::default()
+        .input(env.input(Some(id.clone()), old_data))
+        .input(env.authority_input())
+        .output(env.output(Some(id)))
+        .output_data(new_data.pack())
+        .cell_dep(env.dapp_info_cell_dep.clone())
+        .build();
+    assert_tx_err_code(env.c(), tx, "upgrade v1 to v2", Error::Unauthorized as i8);
+}
+
+#[test]
+fn test_version_decreased() {
+    let mut env = Setup::new();
+
+    let id = Bytes::from(vec![1u8; 32]);
+    let authority = env.authority_lock_script.clone();
+    let old_data = definition_v2(
+        "test",
+        env.dapp_info_hash,
+        [0u8; 32],
+        (1, 2, 0),
+        Some(&authority),
+    );
+    let new_data = definition_v2(
+        "test",
+        env.dapp_info_hash,
+        [0u8; 32],
+        (1, 1, 9),
+        Some(&authority),
+    );
+    let tx = TransactionBuilder::default()
+        .input(env.input(Some(id.clone()), old_data))
+        .input(env.authority_input())
+        .output(env.output(Some(id)))
+        .output_data(new_data.pack())
+        .cell_dep(env.dapp_info_cell_dep.clone())
+        .build();
+    assert_tx_err_code(
+        env.c(),
+        tx,
+        "version decreased",
+        Error::VersionDecreased as i8,
+    );
 }
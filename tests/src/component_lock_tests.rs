@@ -16,6 +16,8 @@ pub struct Setup {
     pub alice_component_lock_script: packed::Script,
     pub bob_owner_lock_script: packed::Script,
     pub bob_component_lock_script: packed::Script,
+    // Uses the compact args format: owner lock hash and mode.
+    pub alice_compact_component_lock_script: packed::Script,
 }
 
 impl Setup {
@@ -43,6 +45,12 @@ impl Setup {
         let bob_component_lock_script = context
             .build_script(&component_lock_out_point, bob_owner_lock_script.as_bytes())
             .expect("script");
+        let alice_compact_component_lock_script = context
+            .build_script(
+                &component_lock_out_point,
+                compact_args(&alice_owner_lock_script, 0),
+            )
+            .expect("script");
 
         Self {
             context,
@@ -53,6 +61,7 @@ impl Setup {
             alice_component_lock_script,
             bob_owner_lock_script,
             bob_component_lock_script,
+            alice_compact_component_lock_script,
         }
     }
 
@@ -61,6 +70,15 @@ impl Setup {
     }
 
     fn input(&mut self, lock: packed::Script, capacity: u64) -> packed::CellInput {
+        self.input_with_since(lock, capacity, 0)
+    }
+
+    fn input_with_since(
+        &mut self,
+        lock: packed::Script,
+        capacity: u64,
+        since: u64,
+    ) -> packed::CellInput {
         let out_point = self.context.create_cell(
             packed::CellOutput::new_builder()
                 .capacity(capacity.pack())
@@ -70,6 +88,30 @@ impl Setup {
         );
         packed::CellInput::new_builder()
             .previous_output(out_point)
+            .since(since.pack())
+            .build()
+    }
+
+    // The component cell uses the component type script with definition seed 1.
+    fn component_input(
+        &mut self,
+        lock: packed::Script,
+        capacity: u64,
+        data: Bytes,
+    ) -> packed::CellInput {
+        let output = self.component_output(lock, capacity);
+        let out_point = self.context.create_cell(output, data);
+        packed::CellInput::new_builder()
+            .previous_output(out_point)
+            .build()
+    }
+
+    fn component_output(&mut self, lock: packed::Script, capacity: u64) -> packed::CellOutput {
+        let component_type = component_type_script(self, 1);
+        packed::CellOutput::new_builder()
+            .capacity(capacity.pack())
+            .lock(lock)
+            .type_(Some(component_type).pack())
             .build()
     }
 
@@ -81,6 +123,19 @@ impl Setup {
     }
 }
 
+fn compact_args(owner_lock_script: &packed::Script, mode: u8) -> Bytes {
+    compact_args_with_fields(owner_lock_script, mode, &[])
+}
+
+fn compact_args_with_fields(owner_lock_script: &packed::Script, mode: u8, fields: &[u8]) -> Bytes {
+    // The format byte
+    let mut args = vec![0u8];
+    args.extend_from_slice(&owner_lock_script.calc_script_hash().as_bytes());
+    args.push(mode);
+    args.extend_from_slice(fields);
+    Bytes::from(args)
+}
+
 #[test]
 fn test_component_lock_same_balance() {
     let mut env = Setup::new();
@@ -107,7 +162,7 @@ fn test_component_lock_insufficient_balance() {
         env.c(),
         tx,
         "insufficient balance",
-        Error::BalanceError as i8,
+        Error::ShrinkWithoutRefund as i8,
     );
 }
 
@@ -148,8 +203,8 @@ fn test_transfer_to_owner_with_insufficient_balance() {
     assert_tx_err_code(
         env.c(),
         tx,
-        "insufficent balance",
-        Error::BalanceError as i8,
+        "insufficent refund",
+        Error::RefundInsufficient as i8,
     );
 }
 
@@ -182,7 +237,760 @@ fn test_transfer_to_owner_with_insufficient_balance_in_multiple_cells() {
     assert_tx_err_code(
         env.c(),
         tx,
-        "insufficent balance",
-        Error::BalanceError as i8,
+        "insufficent refund",
+        Error::RefundInsufficient as i8,
+    );
+}
+
+#[test]
+fn test_component_lock_grow() {
+    let mut env = Setup::new();
+
+    let tx = TransactionBuilder::default()
+        .input(env.input(env.alice_component_lock_script.clone(), 200u64))
+        .input(env.input(env.bob_owner_lock_script.clone(), 100u64))
+        .output(env.output(env.alice_component_lock_script.clone(), 300u64))
+        .output_data(Bytes::new().pack())
+        .build();
+    assert_tx_ok(env.c(), tx, "grow sponsored by bob");
+}
+
+#[test]
+fn test_component_lock_shrink_with_refund() {
+    let mut env = Setup::new();
+
+    let tx = TransactionBuilder::default()
+        .input(env.input(env.alice_component_lock_script.clone(), 300u64))
+        .output(env.output(env.alice_component_lock_script.clone(), 200u64))
+        .output(env.output(env.alice_owner_lock_script.clone(), 100u64))
+        .outputs_data(vec![Bytes::new(); 2].pack())
+        .build();
+    assert_tx_ok(env.c(), tx, "shrink with refund");
+}
+
+#[test]
+fn test_component_lock_shrink_with_insufficient_refund() {
+    let mut env = Setup::new();
+
+    let tx = TransactionBuilder::default()
+        .input(env.input(env.alice_component_lock_script.clone(), 300u64))
+        .output(env.output(env.alice_component_lock_script.clone(), 200u64))
+        .output(env.output(env.alice_owner_lock_script.clone(), 99u64))
+        .outputs_data(vec![Bytes::new(); 2].pack())
+        .build();
+    assert_tx_err_code(
+        env.c(),
+        tx,
+        "shrink with insufficient refund",
+        Error::RefundInsufficient as i8,
+    );
+}
+
+#[test]
+fn test_compact_args_same_balance() {
+    let mut env = Setup::new();
+
+    let tx = TransactionBuilder::default()
+        .input(env.input(env.alice_compact_component_lock_script.clone(), 200u64))
+        .output(env.output(env.alice_compact_component_lock_script.clone(), 200u64))
+        .output_data(Bytes::new().pack())
+        .build();
+    assert_tx_ok(env.c(), tx, "same balance");
+}
+
+#[test]
+fn test_compact_args_insufficient_balance() {
+    let mut env = Setup::new();
+
+    let tx = TransactionBuilder::default()
+        .input(env.input(env.alice_compact_component_lock_script.clone(), 201u64))
+        .output(env.output(env.alice_compact_component_lock_script.clone(), 200u64))
+        .output_data(Bytes::new().pack())
+        .build();
+    assert_tx_err_code(
+        env.c(),
+        tx,
+        "insufficient balance",
+        Error::ShrinkWithoutRefund as i8,
+    );
+}
+
+#[test]
+fn test_compact_args_owner_unlocking() {
+    let mut env = Setup::new();
+
+    let tx = TransactionBuilder::default()
+        .input(env.input(env.alice_owner_lock_script.clone(), 200u64))
+        .input(env.input(env.alice_compact_component_lock_script.clone(), 300u64))
+        .output(env.output(env.bob_owner_lock_script.clone(), 100u64))
+        .output_data(Bytes::new().pack())
+        .build();
+    assert_tx_ok(env.c(), tx, "transfer some to bob and burn the rest");
+}
+
+#[test]
+fn test_compact_args_transfer_to_owner() {
+    let mut env = Setup::new();
+
+    let tx = TransactionBuilder::default()
+        .input(env.input(env.alice_compact_component_lock_script.clone(), 300u64))
+        .output(env.output(env.alice_owner_lock_script.clone(), 300u64))
+        .output_data(Bytes::new().pack())
+        .build();
+    assert_tx_ok(env.c(), tx, "transfer to owner");
+}
+
+#[test]
+fn test_invalid_args() {
+    let mut env = Setup::new();
+
+    let mut unknown_format = compact_args(&env.alice_owner_lock_script, 0).to_vec();
+    unknown_format[0] = 1;
+    let invalid_args = [
+        Bytes::from(vec![0u8; 32]),
+        compact_args(&env.alice_owner_lock_script, 0x80),
+        Bytes::from(unknown_format),
+    ];
+    for args in invalid_args {
+        let lock = env
+            .context
+            .build_script(&env.component_lock_out_point, args)
+            .expect("script");
+        let tx = TransactionBuilder::default()
+            .input(env.input(lock.clone(), 200u64))
+            .output(env.output(lock, 200u64))
+            .output_data(Bytes::new().pack())
+            .build();
+        assert_tx_err_code(env.c(), tx, "invalid args", Error::InvalidArgs as i8);
+    }
+}
+
+const MODE_EXPIRY: u8 = 0x01;
+const MODE_FEE_BUDGET: u8 = 0x02;
+const MODE_RESTRICT_TYPE: u8 = 0x04;
+const MODE_RESTRICT_DEFINITION: u8 = 0x08;
+const MODE_SPONSOR: u8 = 0x10;
+const SINCE_EPOCH_FLAG: u64 = 0x2000_0000_0000_0000;
+const SINCE_TIMESTAMP_FLAG: u64 = 0x4000_0000_0000_0000;
+
+fn epoch_since(number: u64, index: u64, length: u64) -> u64 {
+    SINCE_EPOCH_FLAG | (length << 40) | (index << 24) | number
+}
+
+fn expiry_lock_script(env: &mut Setup, expiry: u64) -> packed::Script {
+    let args = compact_args_with_fields(
+        &env.alice_owner_lock_script,
+        MODE_EXPIRY,
+        &expiry.to_le_bytes(),
+    );
+    env.context
+        .build_script(&env.component_lock_out_point, args)
+        .expect("script")
+}
+
+#[test]
+fn test_update_before_expiry() {
+    let mut env = Setup::new();
+    let lock = expiry_lock_script(&mut env, 1000);
+
+    let tx = TransactionBuilder::default()
+        .input(env.input_with_since(lock.clone(), 200u64, 999))
+        .output(env.output(lock, 200u64))
+        .output_data(Bytes::new().pack())
+        .build();
+    assert_tx_ok(env.c(), tx, "capacity preserving update before expiry");
+}
+
+#[test]
+fn test_reclaim_before_expiry() {
+    let mut env = Setup::new();
+    let lock = expiry_lock_script(&mut env, 1000);
+
+    let tx = TransactionBuilder::default()
+        .input(env.input(env.alice_owner_lock_script.clone(), 200u64))
+        .input(env.input_with_since(lock, 300u64, 999))
+        .output(env.output(env.alice_owner_lock_script.clone(), 500u64))
+        .output_data(Bytes::new().pack())
+        .build();
+    assert_tx_err_code(
+        env.c(),
+        tx,
+        "reclaim before expiry",
+        Error::NotExpired as i8,
+    );
+}
+
+#[test]
+fn test_reclaim_after_expiry() {
+    let mut env = Setup::new();
+    let lock = expiry_lock_script(&mut env, 1000);
+
+    let tx = TransactionBuilder::default()
+        .input(env.input(env.alice_owner_lock_script.clone(), 200u64))
+        .input(env.input_with_since(lock, 300u64, 1000))
+        .output(env.output(env.bob_owner_lock_script.clone(), 100u64))
+        .output_data(Bytes::new().pack())
+        .build();
+    assert_tx_ok(env.c(), tx, "reclaim after expiry");
+}
+
+#[test]
+fn test_transfer_to_owner_after_expiry() {
+    let mut env = Setup::new();
+    let lock = expiry_lock_script(&mut env, 1000);
+
+    let tx = TransactionBuilder::default()
+        .input(env.input_with_since(lock, 300u64, 1001))
+        .output(env.output(env.alice_owner_lock_script.clone(), 300u64))
+        .output_data(Bytes::new().pack())
+        .build();
+    assert_tx_ok(env.c(), tx, "transfer to owner after expiry");
+}
+
+#[test]
+fn test_reclaim_with_since_in_another_metric() {
+    let mut env = Setup::new();
+    let lock = expiry_lock_script(&mut env, 1000);
+
+    let tx = TransactionBuilder::default()
+        .input(env.input(env.alice_owner_lock_script.clone(), 200u64))
+        .input(env.input_with_since(lock, 300u64, SINCE_TIMESTAMP_FLAG | 2000))
+        .output(env.output(env.alice_owner_lock_script.clone(), 500u64))
+        .output_data(Bytes::new().pack())
+        .build();
+    assert_tx_err_code(
+        env.c(),
+        tx,
+        "since in another metric",
+        Error::NotExpired as i8,
+    );
+}
+
+#[test]
+fn test_reclaim_after_epoch_expiry() {
+    let mut env = Setup::new();
+    let lock = expiry_lock_script(&mut env, epoch_since(10, 1, 2));
+
+    for (since, expired) in [
+        (epoch_since(10, 2, 4), true),
+        (epoch_since(11, 0, 1), true),
+        (epoch_since(10, 1, 3), false),
+        // CKB treats 10 0/0 as 10 0/1
+        (epoch_since(10, 0, 0), false),
+    ] {
+        let tx = TransactionBuilder::default()
+            .input(env.input(env.alice_owner_lock_script.clone(), 200u64))
+            .input(env.input_with_since(lock.clone(), 300u64, since))
+            .output(env.output(env.alice_owner_lock_script.clone(), 500u64))
+            .output_data(Bytes::new().pack())
+            .build();
+        if expired {
+            assert_tx_ok(env.c(), tx, "reclaim after epoch expiry");
+        } else {
+            assert_tx_err_code(
+                env.c(),
+                tx,
+                "reclaim before epoch expiry",
+                Error::NotExpired as i8,
+            );
+        }
+    }
+}
+
+// The fee budget requires the restrict type mode.
+fn fee_budget_lock_script(env: &mut Setup, fee_budget: u64) -> packed::Script {
+    let mut fields = fee_budget.to_le_bytes().to_vec();
+    fields.extend_from_slice(&restrict_type_fields(env));
+    let args = compact_args_with_fields(
+        &env.alice_owner_lock_script,
+        MODE_FEE_BUDGET | MODE_RESTRICT_TYPE,
+        &fields,
+    );
+    env.context
+        .build_script(&env.component_lock_out_point, args)
+        .expect("script")
+}
+
+#[test]
+fn test_fee_budget_requires_restrict_type() {
+    let mut env = Setup::new();
+    let args = compact_args_with_fields(
+        &env.alice_owner_lock_script,
+        MODE_FEE_BUDGET,
+        &10u64.to_le_bytes(),
+    );
+    let lock = env
+        .context
+        .build_script(&env.component_lock_out_point, args)
+        .expect("script");
+
+    let tx = TransactionBuilder::default()
+        .input(env.component_input(lock.clone(), 200u64, Bytes::from(vec![1u8])))
+        .output(env.component_output(lock, 190u64))
+        .output_data(Bytes::from(vec![2u8]).pack())
+        .build();
+    assert_tx_err_code(env.c(), tx, "invalid args", Error::InvalidArgs as i8);
+}
+
+#[test]
+fn test_pay_fee_within_budget() {
+    let mut env = Setup::new();
+    let lock = fee_budget_lock_script(&mut env, 10);
+
+    let tx = TransactionBuilder::default()
+        .input(env.component_input(lock.clone(), 200u64, Bytes::from(vec![1u8])))
+        .output(env.component_output(lock, 190u64))
+        .output_data(Bytes::from(vec![2u8]).pack())
+        .build();
+    assert_tx_ok(env.c(), tx, "pay fee within budget");
+}
+
+#[test]
+fn test_pay_fee_without_component_update() {
+    let mut env = Setup::new();
+    let lock = fee_budget_lock_script(&mut env, 10);
+
+    // Without updating the component, the cells could be submitted again and again to drain the
+    // capacity.
+    let tx = TransactionBuilder::default()
+        .input(env.component_input(lock.clone(), 200u64, Bytes::from(vec![1u8])))
+        .output(env.component_output(lock, 190u64))
+        .output_data(Bytes::from(vec![1u8]).pack())
+        .build();
+    assert_tx_err_code(
+        env.c(),
+        tx,
+        "pay fee without component update",
+        Error::ShrinkWithoutRefund as i8,
+    );
+}
+
+#[test]
+fn test_pay_fee_exceeding_budget() {
+    let mut env = Setup::new();
+    let lock = fee_budget_lock_script(&mut env, 10);
+
+    let tx = TransactionBuilder::default()
+        .input(env.component_input(lock.clone(), 200u64, Bytes::from(vec![1u8])))
+        .output(env.component_output(lock, 189u64))
+        .output_data(Bytes::from(vec![2u8]).pack())
+        .build();
+    assert_tx_err_code(
+        env.c(),
+        tx,
+        "pay fee exceeding budget",
+        Error::FeeExceeded as i8,
+    );
+}
+
+#[test]
+fn test_shrink_with_refund_and_fee() {
+    let mut env = Setup::new();
+    let lock = fee_budget_lock_script(&mut env, 10);
+
+    let tx = TransactionBuilder::default()
+        .input(env.component_input(lock.clone(), 300u64, Bytes::from(vec![1u8])))
+        .output(env.component_output(lock, 200u64))
+        .output_data(Bytes::from(vec![2u8]).pack())
+        .output(env.output(env.alice_owner_lock_script.clone(), 90u64))
+        .output_data(Bytes::new().pack())
+        .build();
+    assert_tx_ok(env.c(), tx, "shrink with refund and fee");
+}
+
+#[test]
+fn test_shrink_with_refund_and_fee_exceeding_budget() {
+    let mut env = Setup::new();
+    let lock = fee_budget_lock_script(&mut env, 10);
+
+    let tx = TransactionBuilder::default()
+        .input(env.component_input(lock.clone(), 300u64, Bytes::from(vec![1u8])))
+        .output(env.component_output(lock, 200u64))
+        .output_data(Bytes::from(vec![2u8]).pack())
+        .output(env.output(env.alice_owner_lock_script.clone(), 89u64))
+        .output_data(Bytes::new().pack())
+        .build();
+    assert_tx_err_code(
+        env.c(),
+        tx,
+        "shrink with refund and fee exceeding budget",
+        Error::FeeExceeded as i8,
+    );
+}
+
+#[test]
+fn test_pay_fee_before_expiry() {
+    let mut env = Setup::new();
+    let mut fields = 1000u64.to_le_bytes().to_vec();
+    fields.extend_from_slice(&10u64.to_le_bytes());
+    fields.extend_from_slice(&restrict_type_fields(&mut env));
+    let args = compact_args_with_fields(
+        &env.alice_owner_lock_script,
+        MODE_EXPIRY | MODE_FEE_BUDGET | MODE_RESTRICT_TYPE,
+        &fields,
+    );
+    let lock = env
+        .context
+        .build_script(&env.component_lock_out_point, args)
+        .expect("script");
+
+    let input = env
+        .component_input(lock.clone(), 200u64, Bytes::from(vec![1u8]))
+        .as_builder()
+        .since(999u64.pack())
+        .build();
+    let tx = TransactionBuilder::default()
+        .input(input)
+        .output(env.component_output(lock, 190u64))
+        .output_data(Bytes::from(vec![2u8]).pack())
+        .build();
+    assert_tx_ok(env.c(), tx, "pay fee before expiry");
+}
+
+// The always-success script stands in for component-type.
+fn component_type_script(env: &mut Setup, definition_seed: u8) -> packed::Script {
+    let mut args = vec![definition_seed; 32];
+    args.push(0);
+    env.context
+        .build_script(&env.owner_lock_out_point, Bytes::from(args))
+        .expect("script")
+}
+
+// The code hash and the hash type of the component type script.
+fn restrict_type_fields(env: &mut Setup) -> Vec<u8> {
+    let component_type = component_type_script(env, 1);
+    let mut fields = component_type.code_hash().as_slice().to_vec();
+    fields.extend_from_slice(component_type.hash_type().as_slice());
+    fields
+}
+
+fn restrict_type_lock_script(env: &mut Setup, restrict_definition: bool) -> packed::Script {
+    let component_type = component_type_script(env, 1);
+    let mut fields = restrict_type_fields(env);
+    let mut mode = MODE_RESTRICT_TYPE;
+    if restrict_definition {
+        fields.extend_from_slice(&component_type.args().raw_data());
+        mode |= MODE_RESTRICT_DEFINITION;
+    }
+    let args = compact_args_with_fields(&env.alice_owner_lock_script, mode, &fields);
+    env.context
+        .build_script(&env.component_lock_out_point, args)
+        .expect("script")
+}
+
+fn typed_output(lock: packed::Script, type_opt: Option<packed::Script>) -> packed::CellOutput {
+    packed::CellOutput::new_builder()
+        .capacity(200u64.pack())
+        .lock(lock)
+        .type_(type_opt.pack())
+        .build()
+}
+
+#[test]
+fn test_restrict_type() {
+    let mut env = Setup::new();
+    let lock = restrict_type_lock_script(&mut env, false);
+
+    for (seed, msg) in [(1, "same definition"), (2, "another definition")] {
+        let component_type = component_type_script(&mut env, seed);
+        let tx = TransactionBuilder::default()
+            .input(env.input(lock.clone(), 200u64))
+            .output(typed_output(lock.clone(), Some(component_type)))
+            .output_data(Bytes::new().pack())
+            .build();
+        assert_tx_ok(env.c(), tx, msg);
+    }
+}
+
+#[test]
+fn test_restrict_type_without_type() {
+    let mut env = Setup::new();
+    let lock = restrict_type_lock_script(&mut env, false);
+
+    let tx = TransactionBuilder::default()
+        .input(env.input(lock.clone(), 200u64))
+        .output(typed_output(lock, None))
+        .output_data(Bytes::from(vec![0u8; 8]).pack())
+        .build();
+    assert_tx_err_code(
+        env.c(),
+        tx,
+        "hijack capacity for storage",
+        Error::InvalidOutputType as i8,
+    );
+}
+
+#[test]
+fn test_restrict_type_with_owner() {
+    let mut env = Setup::new();
+    let lock = restrict_type_lock_script(&mut env, false);
+
+    let tx = TransactionBuilder::default()
+        .input(env.input(env.alice_owner_lock_script.clone(), 200u64))
+        .input(env.input(lock.clone(), 200u64))
+        .output(typed_output(lock, None))
+        .output_data(Bytes::new().pack())
+        .build();
+    assert_tx_err_code(
+        env.c(),
+        tx,
+        "restriction applies to owner",
+        Error::InvalidOutputType as i8,
+    );
+}
+
+#[test]
+fn test_restrict_definition() {
+    let mut env = Setup::new();
+    let lock = restrict_type_lock_script(&mut env, true);
+
+    let component_type = component_type_script(&mut env, 1);
+    let tx = TransactionBuilder::default()
+        .input(env.input(lock.clone(), 200u64))
+        .output(typed_output(lock.clone(), Some(component_type)))
+        .output_data(Bytes::new().pack())
+        .build();
+    assert_tx_ok(env.c(), tx, "same definition");
+
+    let component_type = component_type_script(&mut env, 2);
+    let tx = TransactionBuilder::default()
+        .input(env.input(lock.clone(), 200u64))
+        .output(typed_output(lock, Some(component_type)))
+        .output_data(Bytes::new().pack())
+        .build();
+    assert_tx_err_code(
+        env.c(),
+        tx,
+        "another definition",
+        Error::InvalidOutputType as i8,
+    );
+}
+
+#[test]
+fn test_restrict_definition_with_args_v2() {
+    let mut env = Setup::new();
+    let lock = restrict_type_lock_script(&mut env, true);
+
+    // ComponentArgsV2 appends the entity id and the flags.
+    for (seed, ok) in [(1u8, true), (2u8, false)] {
+        let mut args = component_type_script(&mut env, seed)
+            .args()
+            .raw_data()
+            .to_vec();
+        args.extend_from_slice(&[3u8; 32]);
+        args.push(0);
+        let component_type = env
+            .context
+            .build_script(&env.owner_lock_out_point, Bytes::from(args))
+            .expect("script");
+        let tx = TransactionBuilder::default()
+            .input(env.input(lock.clone(), 200u64))
+            .output(typed_output(lock.clone(), Some(component_type)))
+            .output_data(Bytes::new().pack())
+            .build();
+        if ok {
+            assert_tx_ok(env.c(), tx, "same definition with args v2");
+        } else {
+            assert_tx_err_code(
+                env.c(),
+                tx,
+                "another definition with args v2",
+                Error::InvalidOutputType as i8,
+            );
+        }
+    }
+}
+
+#[test]
+fn test_restrict_definition_requires_restrict_type() {
+    let mut env = Setup::new();
+    let args = compact_args_with_fields(
+        &env.alice_owner_lock_script,
+        MODE_RESTRICT_DEFINITION,
+        &[0u8; 33],
+    );
+    let lock = env
+        .context
+        .build_script(&env.component_lock_out_point, args)
+        .expect("script");
+
+    let tx = TransactionBuilder::default()
+        .input(env.input(lock.clone(), 200u64))
+        .output(env.output(lock, 200u64))
+        .output_data(Bytes::new().pack())
+        .build();
+    assert_tx_err_code(env.c(), tx, "invalid args", Error::InvalidArgs as i8);
+}
+
+// Alice is the owner and Bob is the sponsor.
+fn sponsor_lock_script(env: &mut Setup, sponsored_capacity: u64) -> packed::Script {
+    let mut fields = env
+        .bob_owner_lock_script
+        .calc_script_hash()
+        .as_slice()
+        .to_vec();
+    fields.extend_from_slice(&sponsored_capacity.to_le_bytes());
+    let args = compact_args_with_fields(&env.alice_owner_lock_script, MODE_SPONSOR, &fields);
+    env.context
+        .build_script(&env.component_lock_out_point, args)
+        .expect("script")
+}
+
+#[test]
+fn test_destroy_sponsored_component() {
+    let mut env = Setup::new();
+    let lock = sponsor_lock_script(&mut env, 200);
+
+    let tx = TransactionBuilder::default()
+        .input(env.input(lock, 300u64))
+        .output(env.output(env.bob_owner_lock_script.clone(), 200u64))
+        .output(env.output(env.alice_owner_lock_script.clone(), 100u64))
+        .outputs_data(vec![Bytes::new(); 2].pack())
+        .build();
+    assert_tx_ok(env.c(), tx, "destroy sponsored component");
+}
+
+#[test]
+fn test_destroy_sponsored_components() {
+    let mut env = Setup::new();
+    let lock = sponsor_lock_script(&mut env, 200);
+
+    // Each cell is sponsored with 200.
+    for (sponsor_refund, owner_refund, ok) in [(400u64, 200u64, true), (200, 400, false)] {
+        let tx = TransactionBuilder::default()
+            .input(env.input(lock.clone(), 300u64))
+            .input(env.input(lock.clone(), 300u64))
+            .output(env.output(env.bob_owner_lock_script.clone(), sponsor_refund))
+            .output(env.output(env.alice_owner_lock_script.clone(), owner_refund))
+            .outputs_data(vec![Bytes::new(); 2].pack())
+            .build();
+        if ok {
+            assert_tx_ok(env.c(), tx, "destroy two sponsored components");
+        } else {
+            assert_tx_err_code(
+                env.c(),
+                tx,
+                "destroy two sponsored components and refund the sponsor for one",
+                Error::SponsorNotRefunded as i8,
+            );
+        }
+    }
+}
+
+#[test]
+fn test_destroy_one_of_sponsored_components() {
+    let mut env = Setup::new();
+    let lock = sponsor_lock_script(&mut env, 200);
+
+    // The kept cell holds its own sponsored capacity, and the owner's share of the destroyed cell
+    // goes to the owner.
+    for (sponsor_refund, owner_refund, ok) in [(200u64, 100u64, true), (100, 200, false)] {
+        let tx = TransactionBuilder::default()
+            .input(env.input(lock.clone(), 300u64))
+            .input(env.input(lock.clone(), 300u64))
+            .output(env.output(lock.clone(), 300u64))
+            .output(env.output(env.bob_owner_lock_script.clone(), sponsor_refund))
+            .output(env.output(env.alice_owner_lock_script.clone(), owner_refund))
+            .outputs_data(vec![Bytes::new(); 3].pack())
+            .build();
+        if ok {
+            assert_tx_ok(env.c(), tx, "destroy one of two sponsored components");
+        } else {
+            assert_tx_err_code(
+                env.c(),
+                tx,
+                "destroy one of two sponsored components and refund the owner share to the sponsor",
+                Error::SponsorNotRefunded as i8,
+            );
+        }
+    }
+}
+
+#[test]
+fn test_destroy_sponsored_component_without_sponsor_refund() {
+    let mut env = Setup::new();
+    let lock = sponsor_lock_script(&mut env, 200);
+
+    let tx = TransactionBuilder::default()
+        .input(env.input(lock, 300u64))
+        .output(env.output(env.bob_owner_lock_script.clone(), 199u64))
+        .output(env.output(env.alice_owner_lock_script.clone(), 101u64))
+        .outputs_data(vec![Bytes::new(); 2].pack())
+        .build();
+    assert_tx_err_code(
+        env.c(),
+        tx,
+        "insufficient sponsor refund",
+        Error::SponsorNotRefunded as i8,
+    );
+}
+
+#[test]
+fn test_destroy_sponsored_component_without_owner_refund() {
+    let mut env = Setup::new();
+    let lock = sponsor_lock_script(&mut env, 200);
+
+    let tx = TransactionBuilder::default()
+        .input(env.input(lock, 300u64))
+        .output(env.output(env.bob_owner_lock_script.clone(), 300u64))
+        .output_data(Bytes::new().pack())
+        .build();
+    assert_tx_err_code(
+        env.c(),
+        tx,
+        "surplus not refunded to owner",
+        Error::ShrinkWithoutRefund as i8,
+    );
+}
+
+#[test]
+fn test_owner_destroy_sponsored_component() {
+    let mut env = Setup::new();
+    let lock = sponsor_lock_script(&mut env, 200);
+
+    let tx = TransactionBuilder::default()
+        .input(env.input(env.alice_owner_lock_script.clone(), 100u64))
+        .input(env.input(lock.clone(), 300u64))
+        .output(env.output(env.bob_owner_lock_script.clone(), 200u64))
+        .output_data(Bytes::new().pack())
+        .build();
+    assert_tx_ok(env.c(), tx, "owner destroys and refunds the sponsor");
+
+    let tx = TransactionBuilder::default()
+        .input(env.input(env.alice_owner_lock_script.clone(), 100u64))
+        .input(env.input(lock, 300u64))
+        .output(env.output(env.alice_owner_lock_script.clone(), 400u64))
+        .output_data(Bytes::new().pack())
+        .build();
+    assert_tx_err_code(
+        env.c(),
+        tx,
+        "owner destroys without refunding the sponsor",
+        Error::SponsorNotRefunded as i8,
     );
 }
+
+#[test]
+fn test_shrink_sponsored_component() {
+    let mut env = Setup::new();
+    let lock = sponsor_lock_script(&mut env, 200);
+
+    // The sponsored capacity stays in the outputs, so the surplus goes to the owner.
+    let tx = TransactionBuilder::default()
+        .input(env.input(lock.clone(), 300u64))
+        .output(env.output(lock.clone(), 200u64))
+        .output(env.output(env.alice_owner_lock_script.clone(), 100u64))
+        .outputs_data(vec![Bytes::new(); 2].pack())
+        .build();
+    assert_tx_ok(env.c(), tx, "shrink to the sponsored capacity");
+
+    // The sponsor gets 50 and the owner gets 100.
+    let tx = TransactionBuilder::default()
+        .input(env.input(lock.clone(), 300u64))
+        .output(env.output(lock, 150u64))
+        .output(env.output(env.bob_owner_lock_script.clone(), 50u64))
+        .output(env.output(env.alice_owner_lock_script.clone(), 100u64))
+        .outputs_data(vec![Bytes::new(); 3].pack())
+        .build();
+    assert_tx_ok(env.c(), tx, "shrink below the sponsored capacity");
+}
@@ -1,12 +1,22 @@
 use super::*;
-use ckb_ecs_schemas::ComponentDefinition;
+use ckb_ecs_delegate::operation;
+use ckb_ecs_schemas::{component_flags, definition_flags, ComponentDefinition, MessageField};
 use ckb_testtool::{
-    ckb_types::{bytes::Bytes, core::TransactionBuilder, packed, prelude::*},
+    ckb_types::{
+        bytes::Bytes,
+        core::{ScriptHashType, TransactionBuilder, TransactionView},
+        packed,
+        prelude::*,
+    },
     context::Context,
 };
 
 include!("../../contracts/component-type/src/error_include.rs");
 
+mod delegate_example {
+    include!("../../contracts/delegate-example/src/error_include.rs");
+}
+
 pub struct Setup {
     pub context: Context,
 
@@ -53,6 +63,39 @@ impl Setup {
             .build()
     }
 
+    fn component_input(&mut self, args: Bytes, data: Bytes) -> packed::CellInput {
+        let output = self.output(args);
+        let out_point = self.context.create_cell(output, data);
+        packed::CellInput::new_builder()
+            .previous_output(out_point)
+            .build()
+    }
+
+    // Returns the entity id and the entity cell dep.
+    fn entity(&mut self, seed: u8) -> ([u8; 32], packed::CellDep) {
+        let (entity, output) = self.entity_output(seed);
+        let out_point = self.context.create_cell(output, Bytes::new());
+        let cell_dep = packed::CellDep::new_builder().out_point(out_point).build();
+        (entity, cell_dep)
+    }
+
+    // Returns the entity id and the entity cell output.
+    fn entity_output(&mut self, seed: u8) -> ([u8; 32], packed::CellOutput) {
+        let entity_type = self
+            .context
+            .build_script(&self.always_success_out_point, Bytes::from(vec![seed; 32]))
+            .expect("script");
+        let entity = ckb_hash(entity_type.as_slice())
+            .try_into()
+            .expect("type hash");
+        let output = packed::CellOutput::new_builder()
+            .capacity(2000u64.pack())
+            .lock(self.always_success_script.clone())
+            .type_(Some(entity_type).pack())
+            .build();
+        (entity, output)
+    }
+
     fn output(&mut self, args: Bytes) -> packed::CellOutput {
         let script = self
             .context
@@ -65,6 +108,23 @@ impl Setup {
             .build()
     }
 
+    // Spawn requires VM version 2.
+    fn spawn_output(&mut self, args: Bytes) -> packed::CellOutput {
+        let script = self
+            .context
+            .build_script_with_hash_type(
+                &self.component_type_out_point,
+                ScriptHashType::Data2,
+                args,
+            )
+            .expect("script");
+        packed::CellOutput::new_builder()
+            .capacity(2000u64.pack())
+            .lock(self.always_success_script.clone())
+            .type_(Some(script).pack())
+            .build()
+    }
+
     fn definition_cell(
         &mut self,
         definition: &ComponentDefinition,
@@ -94,6 +154,44 @@ fn create_definition(delegate: packed::Script) -> ComponentDefinition {
         .build()
 }
 
+fn create_definition_v2(
+    delegates: Vec<packed::Script>,
+    flags: u32,
+    value_schema_hash: [u8; 32],
+    entity_type: Option<ckb_ecs_schemas::EntityType>,
+) -> ComponentDefinition {
+    use ckb_ecs_schemas::{
+        ComponentDefinitionBuilder, ComponentDefinitionV2Builder, ScriptVecBuilder, SemVerBuilder,
+    };
+
+    let delegates = ScriptVecBuilder::default()
+        .extend(delegates.into_iter().map(|delegate| {
+            ckb_ecs_schemas::Script::from_slice(delegate.as_slice()).expect("compatible")
+        }))
+        .build();
+    let version = SemVerBuilder::default()
+        .major(1u32.into())
+        .minor(0u32.into())
+        .patch(0u32.into())
+        .build();
+    let definition_v2 = ComponentDefinitionV2Builder::default()
+        .component_name("test".into())
+        .info_hash([0u8; 32].into())
+        .value_schema_hash(value_schema_hash.into())
+        .version(version)
+        .delegates(delegates)
+        .flags(flags.into())
+        .entity_type(
+            ckb_ecs_schemas::EntityTypeOpt::new_builder()
+                .set(entity_type)
+                .build(),
+        )
+        .build();
+    ComponentDefinitionBuilder::default()
+        .set(definition_v2)
+        .build()
+}
+
 #[test]
 fn test_invalid_args_len() {
     let mut env = Setup::new();
@@ -150,6 +248,65 @@ fn test_component_definition_found_by_data_hash() {
     assert_tx_ok(env.c(), tx, "found by data hash");
 }
 
+#[test]
+fn test_component_definition_found_by_data1_and_data2_hash() {
+    let mut env = Setup::new();
+
+    let definition = create_definition(env.always_success_script.clone());
+    let definition_cell = env.definition_cell(&definition, None);
+
+    for (hash_type, msg) in [(2u8, "found by data1 hash"), (4u8, "found by data2 hash")] {
+        let mut args = ckb_hash(definition.as_slice());
+        args.push(hash_type);
+
+        let tx = TransactionBuilder::default()
+            .input(env.input())
+            .output(env.output(Bytes::from(args)))
+            .output_data(Bytes::new().pack())
+            .cell_dep(
+                packed::CellDepBuilder::default()
+                    .out_point(definition_cell.clone())
+                    .dep_type(0u8.into())
+                    .build(),
+            )
+            .build();
+
+        assert_tx_ok(env.c(), tx, msg);
+    }
+}
+
+#[test]
+fn test_invalid_args() {
+    let mut env = Setup::new();
+
+    let definition = create_definition(env.always_success_script.clone());
+    let definition_cell = env.definition_cell(&definition, None);
+
+    let mut trailing_bytes_args = ckb_hash(definition.as_slice());
+    trailing_bytes_args.extend_from_slice(&[0, 0]);
+    let mut unknown_hash_type_args = ckb_hash(definition.as_slice());
+    unknown_hash_type_args.push(3);
+
+    for (args, msg) in [
+        (trailing_bytes_args, "trailing bytes"),
+        (unknown_hash_type_args, "unknown hash type"),
+    ] {
+        let tx = TransactionBuilder::default()
+            .input(env.input())
+            .output(env.output(Bytes::from(args)))
+            .output_data(Bytes::new().pack())
+            .cell_dep(
+                packed::CellDepBuilder::default()
+                    .out_point(definition_cell.clone())
+                    .dep_type(0u8.into())
+                    .build(),
+            )
+            .build();
+
+        assert_tx_err_code(env.c(), tx, msg, Error::InvalidArgs as i8);
+    }
+}
+
 #[test]
 fn test_component_definition_found_by_type_hash() {
     let mut env = Setup::new();
@@ -215,3 +372,1402 @@ fn test_component_definition_delegate_err() {
 
     assert_tx_err_message(env.c(), tx, "delegate err", "VM Internal Error");
 }
+
+fn definition_v2_tx(env: &mut Setup, definition: &ComponentDefinition) -> TransactionView {
+    let definition_cell = env.definition_cell(definition, None);
+
+    let mut args = ckb_hash(definition.as_slice());
+    args.push(0);
+
+    TransactionBuilder::default()
+        .input(env.input())
+        .output(env.output(Bytes::from(args)))
+        .output_data(Bytes::new().pack())
+        .cell_dep(
+            packed::CellDepBuilder::default()
+                .out_point(definition_cell)
+                .dep_type(0u8.into())
+                .build(),
+        )
+        .build()
+}
+
+#[test]
+fn test_component_definition_v2_without_delegates() {
+    let mut env = Setup::new();
+
+    let definition = create_definition_v2(vec![], 0, [0u8; 32], None);
+    let tx = definition_v2_tx(&mut env, &definition);
+
+    assert_tx_ok(env.c(), tx, "v2 without delegates");
+}
+
+#[test]
+fn test_component_definition_v2_with_delegate() {
+    let mut env = Setup::new();
+
+    let definition =
+        create_definition_v2(vec![env.always_success_script.clone()], 0, [0u8; 32], None);
+    let tx = definition_v2_tx(&mut env, &definition);
+
+    assert_tx_ok(env.c(), tx, "v2 with delegate");
+}
+
+const ALL_OPERATIONS: u8 =
+    operation::CREATE | operation::UPDATE | operation::DESTROY | operation::TRANSFER;
+
+// The delegate-example args are the mask of the allowed operations followed by the component name.
+fn delegate_example_args(operations: u8, component_name: &str) -> Bytes {
+    let mut args = vec![operations];
+    args.extend_from_slice(component_name.as_bytes());
+    Bytes::from(args)
+}
+
+// Uses V1 if flags is None, otherwise uses V2 and runs component-type in VM version 2.
+fn delegate_example_tx(
+    env: &mut Setup,
+    delegates_args: &[&str],
+    flags: Option<u32>,
+) -> TransactionView {
+    let delegate_out_point = env
+        .context
+        .deploy_cell(Loader::default().load_binary("delegate-example"));
+    let delegates: Vec<_> = delegates_args
+        .iter()
+        .map(|args| {
+            env.context
+                .build_script(
+                    &delegate_out_point,
+                    delegate_example_args(ALL_OPERATIONS, args),
+                )
+                .expect("script")
+        })
+        .collect();
+    let definition = match flags {
+        Some(flags) => create_definition_v2(delegates, flags, [0u8; 32], None),
+        None => create_definition(delegates[0].clone()),
+    };
+    let definition_cell = env.definition_cell(&definition, None);
+
+    let mut args = ckb_hash(definition.as_slice());
+    args.push(0);
+    let output = match flags {
+        Some(_) => env.spawn_output(Bytes::from(args)),
+        None => env.output(Bytes::from(args)),
+    };
+
+    TransactionBuilder::default()
+        .input(env.input())
+        .output(output)
+        .output_data(Bytes::new().pack())
+        .cell_dep(
+            packed::CellDepBuilder::default()
+                .out_point(definition_cell)
+                .dep_type(0u8.into())
+                .build(),
+        )
+        .cell_dep(
+            packed::CellDepBuilder::default()
+                .out_point(delegate_out_point)
+                .dep_type(0u8.into())
+                .build(),
+        )
+        .build()
+}
+
+#[test]
+fn test_delegate_context() {
+    let mut env = Setup::new();
+
+    let tx = delegate_example_tx(&mut env, &["test"], None);
+
+    assert_tx_ok(env.c(), tx, "delegate context");
+}
+
+#[test]
+fn test_delegate_context_component_name_mismatch() {
+    let mut env = Setup::new();
+
+    let tx = delegate_example_tx(&mut env, &["other"], None);
+
+    assert_tx_err_code(
+        env.c(),
+        tx,
+        "delegate context with another component name",
+        delegate_example::Error::ComponentNameMismatch as i8,
+    );
+}
+
+#[test]
+fn test_spawn_delegate() {
+    let mut env = Setup::new();
+
+    let tx = delegate_example_tx(&mut env, &["test"], Some(definition_flags::SPAWN));
+
+    assert_tx_ok(env.c(), tx, "spawn delegate");
+}
+
+#[test]
+fn test_spawn_delegate_failed() {
+    let mut env = Setup::new();
+
+    let tx = delegate_example_tx(&mut env, &["other"], Some(definition_flags::SPAWN));
+
+    assert_tx_err_code(
+        env.c(),
+        tx,
+        "spawn delegate failed",
+        Error::DelegateFailed as i8,
+    );
+}
+
+#[test]
+fn test_multiple_delegates() {
+    let mut env = Setup::new();
+
+    let tx = delegate_example_tx(&mut env, &["test", "test", "test"], Some(0));
+
+    assert_tx_ok(env.c(), tx, "multiple delegates");
+}
+
+#[test]
+fn test_multiple_delegates_first_failed() {
+    let mut env = Setup::new();
+
+    let tx = delegate_example_tx(&mut env, &["other", "test"], Some(0));
+
+    assert_tx_err_code(
+        env.c(),
+        tx,
+        "first delegate failed",
+        Error::DelegateFailed as i8,
+    );
+}
+
+#[test]
+fn test_multiple_delegates_last_failed() {
+    let mut env = Setup::new();
+
+    let tx = delegate_example_tx(&mut env, &["test", "other"], Some(0));
+
+    // The last delegate is exec'ed
+    assert_tx_err_code(
+        env.c(),
+        tx,
+        "last delegate failed",
+        delegate_example::Error::ComponentNameMismatch as i8,
+    );
+}
+
+#[test]
+fn test_multiple_delegates_spawn_last_failed() {
+    let mut env = Setup::new();
+
+    let tx = delegate_example_tx(&mut env, &["test", "other"], Some(definition_flags::SPAWN));
+
+    assert_tx_err_code(
+        env.c(),
+        tx,
+        "last spawned delegate failed",
+        Error::DelegateFailed as i8,
+    );
+}
+
+// Runs delegate-example which only allows `operations` in a tx doing `op`.
+fn lifecycle_tx(env: &mut Setup, operations: u8, op: u8) -> TransactionView {
+    let delegate_out_point = env
+        .context
+        .deploy_cell(Loader::default().load_binary("delegate-example"));
+    let delegate = env
+        .context
+        .build_script(
+            &delegate_out_point,
+            delegate_example_args(operations, "test"),
+        )
+        .expect("script");
+    let definition = create_definition(delegate);
+    let definition_cell = env.definition_cell(&definition, None);
+
+    let mut args = ckb_hash(definition.as_slice());
+    args.push(0);
+    let args = Bytes::from(args);
+
+    let builder = match op {
+        operation::CREATE => TransactionBuilder::default()
+            .input(env.input())
+            .output(env.output(args))
+            .output_data(Bytes::new().pack()),
+        operation::UPDATE | operation::TRANSFER => {
+            let output_data = if op == operation::UPDATE {
+                vec![2u8]
+            } else {
+                vec![1u8]
+            };
+            TransactionBuilder::default()
+                .input(env.component_input(args.clone(), Bytes::from(vec![1u8])))
+                .output(env.output(args))
+                .output_data(Bytes::from(output_data).pack())
+        }
+        operation::DESTROY => TransactionBuilder::default()
+            .input(env.component_input(args, Bytes::from(vec![1u8])))
+            .output(
+                packed::CellOutput::new_builder()
+                    .capacity(2000u64.pack())
+                    .lock(env.always_success_script.clone())
+                    .build(),
+            )
+            .output_data(Bytes::new().pack()),
+        _ => panic!("unknown operation"),
+    };
+
+    builder
+        .cell_dep(
+            packed::CellDepBuilder::default()
+                .out_point(definition_cell)
+                .dep_type(0u8.into())
+                .build(),
+        )
+        .cell_dep(
+            packed::CellDepBuilder::default()
+                .out_point(delegate_out_point)
+                .dep_type(0u8.into())
+                .build(),
+        )
+        .build()
+}
+
+#[test]
+fn test_delegate_operations() {
+    for op in [
+        operation::CREATE,
+        operation::UPDATE,
+        operation::DESTROY,
+        operation::TRANSFER,
+    ] {
+        let mut env = Setup::new();
+        let tx = lifecycle_tx(&mut env, op, op);
+        assert_tx_ok(env.c(), tx, &format!("operation {}", op));
+    }
+}
+
+#[test]
+fn test_delegate_operation_not_allowed() {
+    for op in [
+        operation::CREATE,
+        operation::UPDATE,
+        operation::DESTROY,
+        operation::TRANSFER,
+    ] {
+        let mut env = Setup::new();
+        let tx = lifecycle_tx(&mut env, ALL_OPERATIONS & !op, op);
+        assert_tx_err_code(
+            env.c(),
+            tx,
+            &format!("operation {} not allowed", op),
+            delegate_example::Error::OperationNotAllowed as i8,
+        );
+    }
+}
+
+// The value is a Uint32
+fn uint32_value_schema() -> Bytes {
+    use ckb_ecs_schemas::{layout::KIND_FIXED, *};
+
+    let nodes = LayoutNodeVecBuilder::default()
+        .push(
+            LayoutNodeBuilder::default()
+                .kind(KIND_FIXED.into())
+                .count(4u32.into())
+                .build(),
+        )
+        .build();
+    ValueSchemaBuilder::default()
+        .nodes(nodes)
+        .build()
+        .as_bytes()
+}
+
+fn value_schema_tx(env: &mut Setup, value: Bytes, with_schema_dep: bool) -> TransactionView {
+    value_schema_tx_with_delegates(env, vec![], 0, value, with_schema_dep)
+}
+
+// Runs component-type in VM version 2 if the flags set SPAWN.
+fn value_schema_tx_with_delegates(
+    env: &mut Setup,
+    delegates: Vec<packed::Script>,
+    flags: u32,
+    value: Bytes,
+    with_schema_dep: bool,
+) -> TransactionView {
+    let schema = uint32_value_schema();
+    let schema_hash = ckb_hash(&schema).try_into().expect("schema hash");
+    let schema_out_point = env.context.create_cell(
+        packed::CellOutput::new_builder()
+            .capacity(2000u64.pack())
+            .lock(env.always_success_script.clone())
+            .build(),
+        schema,
+    );
+
+    let definition = create_definition_v2(delegates, flags, schema_hash, None);
+    let definition_cell = env.definition_cell(&definition, None);
+    let mut args = ckb_hash(definition.as_slice());
+    args.push(0);
+    let output = if flags & definition_flags::SPAWN != 0 {
+        env.spawn_output(Bytes::from(args))
+    } else {
+        env.output(Bytes::from(args))
+    };
+
+    let mut builder = TransactionBuilder::default()
+        .input(env.input())
+        .output(output)
+        .output_data(value.pack())
+        .cell_dep(
+            packed::CellDepBuilder::default()
+                .out_point(definition_cell)
+                .dep_type(0u8.into())
+                .build(),
+        );
+    if with_schema_dep {
+        builder = builder.cell_dep(
+            packed::CellDepBuilder::default()
+                .out_point(schema_out_point)
+                .dep_type(0u8.into())
+                .build(),
+        );
+    }
+    builder.build()
+}
+
+#[test]
+fn test_valid_value() {
+    let mut env = Setup::new();
+
+    let tx = value_schema_tx(&mut env, Bytes::from(vec![0u8; 4]), true);
+
+    assert_tx_ok(env.c(), tx, "valid value");
+}
+
+#[test]
+fn test_invalid_value() {
+    let mut env = Setup::new();
+
+    let tx = value_schema_tx(&mut env, Bytes::from(vec![0u8; 3]), true);
+
+    assert_tx_err_code(env.c(), tx, "invalid value", Error::InvalidValue as i8);
+}
+
+// The spawned delegate runs before component-type validates the value.
+fn spawn_value_tx(env: &mut Setup, component_name: &str, value: Bytes) -> TransactionView {
+    let delegate_out_point = env
+        .context
+        .deploy_cell(Loader::default().load_binary("delegate-example"));
+    let delegate = env
+        .context
+        .build_script(
+            &delegate_out_point,
+            delegate_example_args(ALL_OPERATIONS, component_name),
+        )
+        .expect("script");
+
+    value_schema_tx_with_delegates(env, vec![delegate], definition_flags::SPAWN, value, true)
+        .as_advanced_builder()
+        .cell_dep(
+            packed::CellDepBuilder::default()
+                .out_point(delegate_out_point)
+                .dep_type(0u8.into())
+                .build(),
+        )
+        .build()
+}
+
+#[test]
+fn test_invalid_value_after_spawn_delegate() {
+    let mut env = Setup::new();
+
+    let tx = spawn_value_tx(&mut env, "test", Bytes::from(vec![0u8; 4]));
+    assert_tx_ok(env.c(), tx, "valid value after spawn delegate");
+
+    let tx = spawn_value_tx(&mut env, "test", Bytes::from(vec![0u8; 3]));
+    assert_tx_err_code(
+        env.c(),
+        tx,
+        "invalid value after spawn delegate",
+        Error::InvalidValue as i8,
+    );
+
+    // The delegate fails first.
+    let tx = spawn_value_tx(&mut env, "other", Bytes::from(vec![0u8; 3]));
+    assert_tx_err_code(
+        env.c(),
+        tx,
+        "spawn delegate failed before value validation",
+        Error::DelegateFailed as i8,
+    );
+}
+
+#[test]
+fn test_value_schema_not_found() {
+    let mut env = Setup::new();
+
+    let tx = value_schema_tx(&mut env, Bytes::from(vec![0u8; 4]), false);
+
+    assert_tx_err_code(
+        env.c(),
+        tx,
+        "value schema not found",
+        Error::ValueSchemaNotFound as i8,
+    );
+}
+
+fn component_data(entity: [u8; 32], value: &[u8]) -> Bytes {
+    use ckb_ecs_schemas::ComponentDataBuilder;
+
+    ComponentDataBuilder::default()
+        .entity(entity.into())
+        .value(value.into())
+        .build()
+        .as_bytes()
+}
+
+// Returns the component type args and the definition cell dep.
+// The entity cells in the tests use always-success as the entity type.
+fn entity_type(env: &Setup) -> ckb_ecs_schemas::EntityType {
+    let script = &env.always_success_script;
+    ckb_ecs_schemas::EntityTypeBuilder::default()
+        .code_hash(
+            ckb_ecs_schemas::Byte32::from_slice(script.code_hash().as_slice()).expect("byte32"),
+        )
+        .hash_type(script.hash_type().as_slice()[0].into())
+        .build()
+}
+
+fn entity_definition(env: &mut Setup) -> (Bytes, packed::CellDep) {
+    let definition = create_definition_v2(
+        vec![],
+        definition_flags::ENTITY,
+        [0u8; 32],
+        Some(entity_type(env)),
+    );
+    let definition_cell = env.definition_cell(&definition, None);
+    let mut args = ckb_hash(definition.as_slice());
+    args.push(0);
+
+    (
+        Bytes::from(args),
+        packed::CellDep::new_builder()
+            .out_point(definition_cell)
+            .build(),
+    )
+}
+
+#[test]
+fn test_create_entity_component() {
+    let mut env = Setup::new();
+
+    let (args, definition_dep) = entity_definition(&mut env);
+    let (entity, entity_dep) = env.entity(1);
+    let tx = TransactionBuilder::default()
+        .input(env.input())
+        .output(env.output(args))
+        .output_data(component_data(entity, &[]).pack())
+        .cell_dep(definition_dep)
+        .cell_dep(entity_dep)
+        .build();
+
+    assert_tx_ok(env.c(), tx, "create entity component");
+}
+
+#[test]
+fn test_entity_not_found() {
+    let mut env = Setup::new();
+
+    let (args, definition_dep) = entity_definition(&mut env);
+    let tx = TransactionBuilder::default()
+        .input(env.input())
+        .output(env.output(args))
+        .output_data(component_data([1u8; 32], &[]).pack())
+        .cell_dep(definition_dep)
+        .build();
+
+    assert_tx_err_code(env.c(), tx, "entity not found", Error::EntityNotFound as i8);
+}
+
+#[test]
+fn test_entity_of_another_type() {
+    let mut env = Setup::new();
+
+    let (args, definition_dep) = entity_definition(&mut env);
+    let other_type = env
+        .context
+        .build_script(&env.component_type_out_point, Bytes::from(vec![1u8; 32]))
+        .expect("script");
+    let entity: [u8; 32] = ckb_hash(other_type.as_slice())
+        .try_into()
+        .expect("type hash");
+    let out_point = env.context.create_cell(
+        packed::CellOutput::new_builder()
+            .capacity(2000u64.pack())
+            .lock(env.always_success_script.clone())
+            .type_(Some(other_type).pack())
+            .build(),
+        Bytes::new(),
+    );
+    let tx = TransactionBuilder::default()
+        .input(env.input())
+        .output(env.output(args))
+        .output_data(component_data(entity, &[]).pack())
+        .cell_dep(definition_dep)
+        .cell_dep(packed::CellDep::new_builder().out_point(out_point).build())
+        .build();
+
+    assert_tx_err_code(
+        env.c(),
+        tx,
+        "entity of another type",
+        Error::EntityNotFound as i8,
+    );
+}
+
+#[test]
+fn test_entity_is_component_itself() {
+    let mut env = Setup::new();
+
+    let (args, definition_dep) = entity_definition(&mut env);
+    let output = env.output(args);
+    let entity: [u8; 32] = ckb_hash(output.type_().to_opt().expect("type").as_slice())
+        .try_into()
+        .expect("type hash");
+    let tx = TransactionBuilder::default()
+        .input(env.input())
+        .output(output)
+        .output_data(component_data(entity, &[]).pack())
+        .cell_dep(definition_dep)
+        .build();
+
+    assert_tx_err_code(
+        env.c(),
+        tx,
+        "entity is the component itself",
+        Error::EntityNotFound as i8,
+    );
+}
+
+#[test]
+fn test_entity_type_required() {
+    let mut env = Setup::new();
+
+    let definition = create_definition_v2(vec![], definition_flags::ENTITY, [0u8; 32], None);
+    let definition_cell = env.definition_cell(&definition, None);
+    let mut args = ckb_hash(definition.as_slice());
+    args.push(0);
+    let (entity, entity_dep) = env.entity(1);
+    let tx = TransactionBuilder::default()
+        .input(env.input())
+        .output(env.output(Bytes::from(args)))
+        .output_data(component_data(entity, &[]).pack())
+        .cell_dep(
+            packed::CellDep::new_builder()
+                .out_point(definition_cell)
+                .build(),
+        )
+        .cell_dep(entity_dep)
+        .build();
+
+    assert_tx_err_code(
+        env.c(),
+        tx,
+        "entity type required",
+        Error::EntityTypeRequired as i8,
+    );
+}
+
+#[test]
+fn test_invalid_component_data() {
+    let mut env = Setup::new();
+
+    let (args, definition_dep) = entity_definition(&mut env);
+    let tx = TransactionBuilder::default()
+        .input(env.input())
+        .output(env.output(args))
+        .output_data(Bytes::new().pack())
+        .cell_dep(definition_dep)
+        .build();
+
+    assert_tx_err_code(
+        env.c(),
+        tx,
+        "invalid component data",
+        Error::InvalidComponentData as i8,
+    );
+}
+
+#[test]
+fn test_update_entity_component() {
+    let mut env = Setup::new();
+
+    let (args, definition_dep) = entity_definition(&mut env);
+    let input = env.component_input(args.clone(), component_data([1u8; 32], &[1]));
+    let tx = TransactionBuilder::default()
+        .input(input)
+        .output(env.output(args))
+        .output_data(component_data([1u8; 32], &[2]).pack())
+        .cell_dep(definition_dep)
+        .build();
+
+    assert_tx_ok(env.c(), tx, "update entity component");
+}
+
+#[test]
+fn test_entity_changed() {
+    let mut env = Setup::new();
+
+    let (args, definition_dep) = entity_definition(&mut env);
+    let (entity, entity_dep) = env.entity(1);
+    let input = env.component_input(args.clone(), component_data([2u8; 32], &[]));
+    let tx = TransactionBuilder::default()
+        .input(input)
+        .output(env.output(args))
+        .output_data(component_data(entity, &[]).pack())
+        .cell_dep(definition_dep)
+        .cell_dep(entity_dep)
+        .build();
+
+    assert_tx_err_code(env.c(), tx, "entity changed", Error::EntityChanged as i8);
+}
+
+#[test]
+fn test_duplicated_entity() {
+    let mut env = Setup::new();
+
+    let (args, definition_dep) = entity_definition(&mut env);
+    let (entity, entity_dep) = env.entity(1);
+    let tx = TransactionBuilder::default()
+        .input(env.input())
+        .output(env.output(args.clone()))
+        .output_data(component_data(entity, &[1]).pack())
+        .output(env.output(args))
+        .output_data(component_data(entity, &[2]).pack())
+        .cell_dep(definition_dep)
+        .cell_dep(entity_dep)
+        .build();
+
+    assert_tx_err_code(
+        env.c(),
+        tx,
+        "duplicated entity",
+        Error::DuplicatedEntity as i8,
+    );
+}
+
+// Returns the ComponentArgsV2 and the definition cell dep.
+fn args_v2_definition(
+    env: &mut Setup,
+    flags: u32,
+    entity: [u8; 32],
+    args_flags: u8,
+) -> (Bytes, packed::CellDep) {
+    use ckb_ecs_schemas::ComponentArgsV2Builder;
+
+    let definition = create_definition_v2(vec![], flags, [0u8; 32], Some(entity_type(env)));
+    let definition_cell = env.definition_cell(&definition, None);
+    let definition_id: [u8; 32] = ckb_hash(definition.as_slice())
+        .try_into()
+        .expect("32 bytes");
+    let args = ComponentArgsV2Builder::default()
+        .code_hash(definition_id.into())
+        .hash_type(0.into())
+        .entity(entity.into())
+        .flags(args_flags.into())
+        .build();
+
+    (
+        args.as_bytes(),
+        packed::CellDep::new_builder()
+            .out_point(definition_cell)
+            .build(),
+    )
+}
+
+#[test]
+fn test_create_component_with_args_v2() {
+    let mut env = Setup::new();
+
+    let (entity, entity_dep) = env.entity(1);
+    for flags in [0, definition_flags::ENTITY] {
+        let (args, definition_dep) = args_v2_definition(&mut env, flags, entity, 0);
+        let tx = TransactionBuilder::default()
+            .input(env.input())
+            .output(env.output(args))
+            .output_data(component_data(entity, &[]).pack())
+            .cell_dep(definition_dep)
+            .cell_dep(entity_dep.clone())
+            .build();
+
+        assert_tx_ok(env.c(), tx, "create component with args v2");
+    }
+}
+
+#[test]
+fn test_args_v2_requires_component_data() {
+    let mut env = Setup::new();
+
+    let (entity, entity_dep) = env.entity(1);
+    let (args, definition_dep) = args_v2_definition(&mut env, 0, entity, 0);
+    let tx = TransactionBuilder::default()
+        .input(env.input())
+        .output(env.output(args))
+        .output_data(Bytes::from(vec![0u8; 4]).pack())
+        .cell_dep(definition_dep)
+        .cell_dep(entity_dep)
+        .build();
+
+    assert_tx_err_code(
+        env.c(),
+        tx,
+        "invalid component data",
+        Error::InvalidComponentData as i8,
+    );
+}
+
+#[test]
+fn test_args_v2_entity_mismatch() {
+    let mut env = Setup::new();
+
+    let (entity, entity_dep) = env.entity(1);
+    let (other_entity, other_entity_dep) = env.entity(2);
+    let (args, definition_dep) = args_v2_definition(&mut env, 0, entity, 0);
+    let tx = TransactionBuilder::default()
+        .input(env.input())
+        .output(env.output(args))
+        .output_data(component_data(other_entity, &[]).pack())
+        .cell_dep(definition_dep)
+        .cell_dep(entity_dep)
+        .cell_dep(other_entity_dep)
+        .build();
+
+    assert_tx_err_code(env.c(), tx, "entity mismatch", Error::EntityMismatch as i8);
+}
+
+#[test]
+fn test_singleton_component() {
+    let mut env = Setup::new();
+
+    let (entity, entity_dep) = env.entity(1);
+    let (args, definition_dep) =
+        args_v2_definition(&mut env, 0, entity, component_flags::SINGLETON);
+    let tx = TransactionBuilder::default()
+        .input(env.input())
+        .output(env.output(args.clone()))
+        .output(env.output(args))
+        .outputs_data(vec![component_data(entity, &[]); 2].pack())
+        .cell_dep(definition_dep)
+        .cell_dep(entity_dep)
+        .build();
+
+    assert_tx_err_code(
+        env.c(),
+        tx,
+        "singleton component",
+        Error::TooManyCells as i8,
+    );
+}
+
+#[test]
+fn test_immutable_component() {
+    let mut env = Setup::new();
+
+    let (entity, entity_dep) = env.entity(1);
+    let (args, definition_dep) =
+        args_v2_definition(&mut env, 0, entity, component_flags::IMMUTABLE);
+
+    let input = env.component_input(args.clone(), component_data(entity, &[1]));
+    let tx = TransactionBuilder::default()
+        .input(input)
+        .output(env.output(args.clone()))
+        .output_data(component_data(entity, &[1]).pack())
+        .cell_dep(definition_dep.clone())
+        .cell_dep(entity_dep.clone())
+        .build();
+    assert_tx_ok(env.c(), tx, "keep immutable component");
+
+    let input = env.component_input(args.clone(), component_data(entity, &[1]));
+    let tx = TransactionBuilder::default()
+        .input(input)
+        .output(env.output(args))
+        .output_data(component_data(entity, &[2]).pack())
+        .cell_dep(definition_dep)
+        .cell_dep(entity_dep)
+        .build();
+    assert_tx_err_code(
+        env.c(),
+        tx,
+        "update immutable component",
+        Error::ComponentImmutable as i8,
+    );
+}
+
+// Returns the ComponentArgsV2 with the type id derived from the entity, and the definition cell
+// dep.
+fn type_id_definition(env: &mut Setup, entity: [u8; 32]) -> (Bytes, packed::CellDep) {
+    use ckb_ecs_schemas::ComponentArgsV2Builder;
+
+    let definition = create_definition_v2(
+        vec![],
+        definition_flags::TYPE_ID,
+        [0u8; 32],
+        Some(entity_type(env)),
+    );
+    let definition_cell = env.definition_cell(&definition, None);
+    let definition_id: [u8; 32] = ckb_hash(definition.as_slice())
+        .try_into()
+        .expect("32 bytes");
+    let type_id: [u8; 32] = ckb_hash(&[definition_id, entity].concat())
+        .try_into()
+        .expect("32 bytes");
+    let args = ComponentArgsV2Builder::default()
+        .code_hash(definition_id.into())
+        .hash_type(0.into())
+        .entity(type_id.into())
+        .flags(component_flags::TYPE_ID.into())
+        .build();
+
+    (
+        args.as_bytes(),
+        packed::CellDep::new_builder()
+            .out_point(definition_cell)
+            .build(),
+    )
+}
+
+#[test]
+fn test_type_id_component() {
+    let mut env = Setup::new();
+
+    let (entity, entity_output) = env.entity_output(1);
+    let (args, definition_dep) = type_id_definition(&mut env, entity);
+    let tx = TransactionBuilder::default()
+        .input(env.input())
+        .output(entity_output)
+        .output_data(Bytes::new().pack())
+        .output(env.output(args.clone()))
+        .output_data(component_data(entity, &[1]).pack())
+        .cell_dep(definition_dep.clone())
+        .build();
+    assert_tx_ok(env.c(), tx, "create type id component with the entity");
+
+    let input = env.component_input(args.clone(), component_data(entity, &[1]));
+    let tx = TransactionBuilder::default()
+        .input(input)
+        .output(env.output(args))
+        .output_data(component_data(entity, &[2]).pack())
+        .cell_dep(definition_dep)
+        .build();
+    assert_tx_ok(env.c(), tx, "update type id component");
+}
+
+#[test]
+fn test_type_id_component_created_again() {
+    let mut env = Setup::new();
+
+    // The entity cell has been created in an earlier transaction.
+    let (entity, entity_dep) = env.entity(1);
+    let (args, definition_dep) = type_id_definition(&mut env, entity);
+    let tx = TransactionBuilder::default()
+        .input(env.input())
+        .output(env.output(args.clone()))
+        .output_data(component_data(entity, &[1]).pack())
+        .cell_dep(definition_dep.clone())
+        .cell_dep(entity_dep)
+        .build();
+    assert_tx_err_code(
+        env.c(),
+        tx,
+        "create type id component for an existing entity",
+        Error::EntityNotCreated as i8,
+    );
+
+    // Transferring the entity cell does not create it.
+    let (_, entity_output) = env.entity_output(1);
+    let entity_out_point = env.context.create_cell(entity_output.clone(), Bytes::new());
+    let tx = TransactionBuilder::default()
+        .input(
+            packed::CellInput::new_builder()
+                .previous_output(entity_out_point)
+                .build(),
+        )
+        .output(entity_output)
+        .output_data(Bytes::new().pack())
+        .output(env.output(args))
+        .output_data(component_data(entity, &[1]).pack())
+        .cell_dep(definition_dep)
+        .build();
+    assert_tx_err_code(
+        env.c(),
+        tx,
+        "create type id component when transferring the entity",
+        Error::EntityNotCreated as i8,
+    );
+}
+
+#[test]
+fn test_type_id_component_of_another_entity() {
+    let mut env = Setup::new();
+
+    let (entity, entity_output) = env.entity_output(1);
+    let (args, definition_dep) = type_id_definition(&mut env, [2u8; 32]);
+    let tx = TransactionBuilder::default()
+        .input(env.input())
+        .output(entity_output)
+        .output_data(Bytes::new().pack())
+        .output(env.output(args))
+        .output_data(component_data(entity, &[1]).pack())
+        .cell_dep(definition_dep)
+        .build();
+    assert_tx_err_code(
+        env.c(),
+        tx,
+        "type id derived from another entity",
+        Error::InvalidTypeID as i8,
+    );
+}
+
+#[test]
+fn test_type_id_required_by_definition() {
+    let mut env = Setup::new();
+
+    let (entity, entity_output) = env.entity_output(1);
+    let (args_v2, definition_dep) =
+        args_v2_definition(&mut env, definition_flags::TYPE_ID, entity, 0);
+    // The first 33 bytes of ComponentArgsV2 are the ComponentArgs.
+    let args_v1 = args_v2.slice(..33);
+    for args in [args_v2, args_v1] {
+        let tx = TransactionBuilder::default()
+            .input(env.input())
+            .output(entity_output.clone())
+            .output_data(Bytes::new().pack())
+            .output(env.output(args))
+            .output_data(component_data(entity, &[1]).pack())
+            .cell_dep(definition_dep.clone())
+            .build();
+        assert_tx_err_code(
+            env.c(),
+            tx,
+            "create component without type id",
+            Error::TypeIDRequired as i8,
+        );
+    }
+}
+
+#[test]
+fn test_type_id_component_too_many_cells() {
+    let mut env = Setup::new();
+
+    let (entity, entity_output) = env.entity_output(1);
+    let (args, definition_dep) = type_id_definition(&mut env, entity);
+    let tx = TransactionBuilder::default()
+        .input(env.input())
+        .output(entity_output)
+        .output_data(Bytes::new().pack())
+        .output(env.output(args.clone()))
+        .output_data(component_data(entity, &[1]).pack())
+        .output(env.output(args))
+        .output_data(component_data(entity, &[1]).pack())
+        .cell_dep(definition_dep)
+        .build();
+    assert_tx_err_code(
+        env.c(),
+        tx,
+        "create two type id components",
+        Error::TooManyCells as i8,
+    );
+}
+
+fn plain_output(env: &mut Setup) -> packed::CellOutput {
+    packed::CellOutput::new_builder()
+        .capacity(2000u64.pack())
+        .lock(env.always_success_script.clone())
+        .build()
+}
+
+#[test]
+fn test_immutable_definition() {
+    let mut env = Setup::new();
+
+    let (entity, entity_dep) = env.entity(1);
+    let (args, definition_dep) =
+        args_v2_definition(&mut env, definition_flags::IMMUTABLE, entity, 0);
+
+    let input = env.component_input(args.clone(), component_data(entity, &[1]));
+    let tx = TransactionBuilder::default()
+        .input(input)
+        .output(env.output(args.clone()))
+        .output_data(component_data(entity, &[2]).pack())
+        .cell_dep(definition_dep.clone())
+        .cell_dep(entity_dep.clone())
+        .build();
+    assert_tx_err_code(
+        env.c(),
+        tx,
+        "update component of immutable definition",
+        Error::ComponentImmutable as i8,
+    );
+
+    let input = env.component_input(args, component_data(entity, &[1]));
+    let tx = TransactionBuilder::default()
+        .input(input)
+        .output(plain_output(&mut env))
+        .output_data(Bytes::new().pack())
+        .cell_dep(definition_dep)
+        .cell_dep(entity_dep)
+        .build();
+    assert_tx_ok(env.c(), tx, "destroy component of immutable definition");
+}
+
+#[test]
+fn test_no_destroy_definition() {
+    let mut env = Setup::new();
+
+    let (entity, entity_dep) = env.entity(1);
+    let (args, definition_dep) = args_v2_definition(
+        &mut env,
+        definition_flags::IMMUTABLE | definition_flags::NO_DESTROY,
+        entity,
+        0,
+    );
+
+    let input = env.component_input(args.clone(), component_data(entity, &[1]));
+    let tx = TransactionBuilder::default()
+        .input(input)
+        .output(env.output(args.clone()))
+        .output_data(component_data(entity, &[1]).pack())
+        .cell_dep(definition_dep.clone())
+        .cell_dep(entity_dep.clone())
+        .build();
+    assert_tx_ok(env.c(), tx, "keep component of no destroy definition");
+
+    let input = env.component_input(args, component_data(entity, &[1]));
+    let tx = TransactionBuilder::default()
+        .input(input)
+        .output(plain_output(&mut env))
+        .output_data(Bytes::new().pack())
+        .cell_dep(definition_dep)
+        .cell_dep(entity_dep)
+        .build();
+    assert_tx_err_code(
+        env.c(),
+        tx,
+        "destroy component of no destroy definition",
+        Error::ComponentIndestructible as i8,
+    );
+}
+
+fn create_definition_with_systems(systems: Vec<[u8; 32]>) -> ComponentDefinition {
+    use ckb_ecs_schemas::{
+        Byte32VecBuilder, ComponentDefinitionBuilder, ComponentDefinitionV2Builder,
+    };
+
+    let systems = Byte32VecBuilder::default()
+        .extend(systems.into_iter().map(Into::into))
+        .build();
+    let definition_v2 = ComponentDefinitionV2Builder::default()
+        .component_name("test".into())
+        .systems(systems)
+        .build();
+    ComponentDefinitionBuilder::default()
+        .set(definition_v2)
+        .build()
+}
+
+fn system_type(env: &mut Setup) -> packed::Script {
+    env.context
+        .build_script(&env.always_success_out_point, Bytes::from(vec![7u8; 32]))
+        .expect("script")
+}
+
+fn system_cell(env: &mut Setup, reads: Vec<[u8; 32]>, writes: Vec<[u8; 32]>) -> packed::OutPoint {
+    use ckb_ecs_schemas::{Byte32VecBuilder, SystemDefinitionBuilder};
+
+    let system_type = system_type(env);
+    let reads = Byte32VecBuilder::default()
+        .extend(reads.into_iter().map(Into::into))
+        .build();
+    let writes = Byte32VecBuilder::default()
+        .extend(writes.into_iter().map(Into::into))
+        .build();
+    let system = SystemDefinitionBuilder::default()
+        .name("system".into())
+        .reads(reads)
+        .writes(writes)
+        .build();
+    env.context.create_cell(
+        packed::CellOutput::new_builder()
+            .capacity(2000u64.pack())
+            .lock(env.always_success_script.clone())
+            .type_(Some(system_type).pack())
+            .build(),
+        system.as_bytes(),
+    )
+}
+
+// Returns the component-type args, the definition cell dep and the definition id.
+fn system_definition(env: &mut Setup) -> (Bytes, packed::CellDep, [u8; 32]) {
+    let system_type_hash = ckb_hash(system_type(env).as_slice())
+        .try_into()
+        .expect("type hash");
+    let definition = create_definition_with_systems(vec![system_type_hash]);
+    let definition_cell = env.definition_cell(&definition, None);
+    let definition_id: [u8; 32] = ckb_hash(definition.as_slice())
+        .try_into()
+        .expect("data hash");
+    let mut args = definition_id.to_vec();
+    args.push(0);
+
+    (
+        Bytes::from(args),
+        packed::CellDep::new_builder()
+            .out_point(definition_cell)
+            .build(),
+        definition_id,
+    )
+}
+
+fn system_tx(env: &mut Setup, with_system: bool, writes_definition: bool) -> TransactionView {
+    let (args, definition_dep, definition_id) = system_definition(env);
+    let writes = if writes_definition {
+        vec![definition_id]
+    } else {
+        vec![[0u8; 32]]
+    };
+    let system_cell = system_cell(env, vec![], writes);
+
+    let mut builder = TransactionBuilder::default()
+        .input(env.input())
+        .output(env.output(args))
+        .output_data(Bytes::new().pack())
+        .cell_dep(definition_dep);
+    if with_system {
+        builder = builder.input(
+            packed::CellInput::new_builder()
+                .previous_output(system_cell)
+                .build(),
+        );
+    }
+    builder.build()
+}
+
+// There's another component in cell deps which the system reads. The system is in inputs or in cell
+// deps.
+fn system_read_tx(env: &mut Setup, declares_read: bool, system_in_inputs: bool) -> TransactionView {
+    let (args, definition_dep, definition_id) = system_definition(env);
+    let read_id = [3u8; 32];
+    let reads = if declares_read { vec![read_id] } else { vec![] };
+    let system_cell = system_cell(env, reads, vec![definition_id]);
+
+    let mut read_args = read_id.to_vec();
+    read_args.push(0);
+    let read_output = env.output(Bytes::from(read_args));
+    let read_cell = env.context.create_cell(read_output, Bytes::new());
+
+    let builder = TransactionBuilder::default()
+        .input(env.input())
+        .output(env.output(args))
+        .output_data(Bytes::new().pack())
+        .cell_dep(definition_dep)
+        .cell_dep(packed::CellDep::new_builder().out_point(read_cell).build());
+    if system_in_inputs {
+        builder
+            .input(
+                packed::CellInput::new_builder()
+                    .previous_output(system_cell)
+                    .build(),
+            )
+            .build()
+    } else {
+        builder
+            .cell_dep(
+                packed::CellDep::new_builder()
+                    .out_point(system_cell)
+                    .build(),
+            )
+            .build()
+    }
+}
+
+#[test]
+fn test_write_by_system() {
+    let mut env = Setup::new();
+
+    let tx = system_tx(&mut env, true, true);
+
+    assert_tx_ok(env.c(), tx, "write by system");
+}
+
+#[test]
+fn test_write_without_system() {
+    let mut env = Setup::new();
+
+    let tx = system_tx(&mut env, false, true);
+
+    assert_tx_err_code(
+        env.c(),
+        tx,
+        "write without system",
+        Error::SystemRequired as i8,
+    );
+}
+
+#[test]
+fn test_write_by_unauthorized_system() {
+    let mut env = Setup::new();
+
+    let tx = system_tx(&mut env, true, false);
+
+    assert_tx_err_code(
+        env.c(),
+        tx,
+        "write by system which does not declare the definition",
+        Error::SystemRequired as i8,
+    );
+}
+
+#[test]
+fn test_write_by_system_in_cell_deps() {
+    let mut env = Setup::new();
+
+    let tx = system_read_tx(&mut env, true, false);
+
+    assert_tx_err_code(
+        env.c(),
+        tx,
+        "write by system in cell deps",
+        Error::SystemRequired as i8,
+    );
+}
+
+#[test]
+fn test_declared_read() {
+    let mut env = Setup::new();
+
+    let tx = system_read_tx(&mut env, true, true);
+
+    assert_tx_ok(env.c(), tx, "read declared by system");
+}
+
+#[test]
+fn test_undeclared_read() {
+    let mut env = Setup::new();
+
+    let tx = system_read_tx(&mut env, false, true);
+
+    assert_tx_err_code(
+        env.c(),
+        tx,
+        "read component which is not declared by the system",
+        Error::UndeclaredRead as i8,
+    );
+}
+
+fn typed_message(target: [u8; 32]) -> ckb_ecs_schemas::TypedMessage {
+    use ckb_ecs_schemas::TypedMessageBuilder;
+
+    TypedMessageBuilder::default()
+        .action("move".into())
+        .target(target.into())
+        .payload((&[1u8, 2, 3] as &[u8]).into())
+        .nonce(0u64.into())
+        .build()
+}
+
+// Returns the tx without witness and the component type script hash.
+fn message_tx(env: &mut Setup) -> (TransactionView, [u8; 32]) {
+    let definition =
+        create_definition_v2(vec![], definition_flags::MESSAGE_REQUIRED, [0u8; 32], None);
+    let definition_cell = env.definition_cell(&definition, None);
+    let mut args = ckb_hash(definition.as_slice());
+    args.push(0);
+
+    let output = env.output(Bytes::from(args));
+    let target = output
+        .type_()
+        .to_opt()
+        .expect("component type")
+        .calc_script_hash()
+        .unpack();
+    let tx = TransactionBuilder::default()
+        .input(env.input())
+        .output(output)
+        .output_data(Bytes::new().pack())
+        .cell_dep(
+            packed::CellDepBuilder::default()
+                .out_point(definition_cell)
+                .dep_type(0u8.into())
+                .build(),
+        )
+        .build();
+    (tx, target)
+}
+
+#[test]
+fn test_typed_message() {
+    let mut env = Setup::new();
+
+    let (tx, target) = message_tx(&mut env);
+    let witness = typed_message(target).to_witness(MessageField::OutputType);
+    let tx = tx
+        .as_advanced_builder()
+        .witness(witness.as_bytes().pack())
+        .build();
+
+    assert_tx_ok(env.c(), tx, "typed message");
+}
+
+#[test]
+fn test_message_required() {
+    let mut env = Setup::new();
+
+    let (tx, _) = message_tx(&mut env);
+
+    assert_tx_err_code(
+        env.c(),
+        tx,
+        "message required",
+        Error::MessageRequired as i8,
+    );
+}
+
+#[test]
+fn test_invalid_message() {
+    let mut env = Setup::new();
+
+    let (tx, _) = message_tx(&mut env);
+    let witness = packed::WitnessArgs::new_builder()
+        .output_type(Some(Bytes::from(vec![0u8; 4])).pack())
+        .build();
+    let tx = tx
+        .as_advanced_builder()
+        .witness(witness.as_bytes().pack())
+        .build();
+
+    assert_tx_err_code(env.c(), tx, "invalid message", Error::InvalidMessage as i8);
+}
+
+#[test]
+fn test_message_target_mismatch() {
+    let mut env = Setup::new();
+
+    let (tx, _) = message_tx(&mut env);
+    let witness = typed_message([0u8; 32]).to_witness(MessageField::OutputType);
+    let tx = tx
+        .as_advanced_builder()
+        .witness(witness.as_bytes().pack())
+        .build();
+
+    assert_tx_err_code(
+        env.c(),
+        tx,
+        "message target mismatch",
+        Error::MessageTargetMismatch as i8,
+    );
+}
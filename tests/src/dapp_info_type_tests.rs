@@ -0,0 +1,268 @@
+use super::*;
+use ckb_testtool::{
+    ckb_types::{bytes::Bytes, core::TransactionBuilder, packed, prelude::*},
+    context::Context,
+};
+
+include!("../../contracts/dapp-info-type/src/error_include.rs");
+
+pub struct Setup {
+    pub context: Context,
+
+    pub dapp_info_type_out_point: packed::OutPoint,
+    pub always_success_out_point: packed::OutPoint,
+
+    pub always_success_script: packed::Script,
+    pub owner_lock_script: packed::Script,
+}
+
+pub fn dapp_info(name: &str, version: u32, components: &[&str], owner: &packed::Script) -> Bytes {
+    use ckb_ecs_schemas::*;
+    use molecule::prelude::*;
+
+    let components = ComponentInfoVecBuilder::default()
+        .extend(components.iter().map(|name| {
+            ComponentInfoBuilder::default()
+                .name((*name).into())
+                .schema((&[] as &[u8]).into())
+                .build()
+        }))
+        .build();
+    let owner: [u8; 32] = ckb_hash(owner.as_slice()).try_into().expect("lock hash");
+
+    DappInfoBuilder::default()
+        .name(name.into())
+        .version(version.into())
+        .components(components)
+        .owner(owner.into())
+        .build()
+        .as_bytes()
+}
+
+impl Setup {
+    fn new() -> Self {
+        let mut context = Context::default();
+
+        let always_success_out_point =
+            context.deploy_cell(Loader::default().load_binary("always-success"));
+        let dapp_info_type_out_point =
+            context.deploy_cell(Loader::default().load_binary("dapp-info-type"));
+
+        let always_success_script = context
+            .build_script(&always_success_out_point, Bytes::new())
+            .expect("script");
+        let owner_lock_script = context
+            .build_script(&always_success_out_point, Bytes::from(vec![42]))
+            .expect("script");
+
+        Self {
+            context,
+
+            dapp_info_type_out_point,
+            always_success_out_point,
+            always_success_script,
+            owner_lock_script,
+        }
+    }
+
+    fn c(&mut self) -> &mut Context {
+        return &mut self.context;
+    }
+
+    fn input(&mut self, id_opt: Option<Bytes>, data: Bytes) -> packed::CellInput {
+        let type_opt = id_opt.map(|id| {
+            self.context
+                .build_script(&self.dapp_info_type_out_point, id)
+                .expect("script")
+        });
+        let out_point = self.context.create_cell(
+            packed::CellOutput::new_builder()
+                .capacity(2000u64.pack())
+                .lock(self.always_success_script.clone())
+                .type_(type_opt.pack())
+                .build(),
+            data,
+        );
+        packed::CellInput::new_builder()
+            .previous_output(out_point)
+            .build()
+    }
+
+    fn owner_input(&mut self) -> packed::CellInput {
+        let out_point = self.context.create_cell(
+            packed::CellOutput::new_builder()
+                .capacity(2000u64.pack())
+                .lock(self.owner_lock_script.clone())
+                .build(),
+            Bytes::new(),
+        );
+        packed::CellInput::new_builder()
+            .previous_output(out_point)
+            .build()
+    }
+
+    fn output(&mut self, id: Bytes) -> packed::CellOutput {
+        let script = self
+            .context
+            .build_script(&self.dapp_info_type_out_point, id)
+            .expect("script");
+        packed::CellOutput::new_builder()
+            .capacity(2000u64.pack())
+            .lock(self.always_success_script.clone())
+            .type_(Some(script).pack())
+            .build()
+    }
+}
+
+#[test]
+fn test_create_dapp_info() {
+    let mut env = Setup::new();
+
+    let input = env.input(None, Bytes::new());
+    let new_id = new_type_id(&input, 0);
+    let data = dapp_info("dapp", 1, &["position", "health"], &env.owner_lock_script);
+    let tx = TransactionBuilder::default()
+        .input(input)
+        .output(env.output(new_id))
+        .output_data(data.pack())
+        .build();
+    assert_tx_ok(env.c(), tx, "create dapp info");
+}
+
+#[test]
+fn test_invalid_data() {
+    let mut env = Setup::new();
+
+    let input = env.input(None, Bytes::new());
+    let new_id = new_type_id(&input, 0);
+    let mut data = dapp_info("dapp", 1, &["position"], &env.owner_lock_script).to_vec();
+    data[2] = !data[2];
+    let tx = TransactionBuilder::default()
+        .input(input)
+        .output(env.output(new_id))
+        .output_data(data.pack())
+        .build();
+    assert_tx_err_code(
+        env.c(),
+        tx,
+        "create dapp info with invalid data",
+        Error::InvalidData as i8,
+    );
+}
+
+#[test]
+fn test_duplicated_component() {
+    let mut env = Setup::new();
+
+    let input = env.input(None, Bytes::new());
+    let new_id = new_type_id(&input, 0);
+    let data = dapp_info("dapp", 1, &["position", "position"], &env.owner_lock_script);
+    let tx = TransactionBuilder::default()
+        .input(input)
+        .output(env.output(new_id))
+        .output_data(data.pack())
+        .build();
+    assert_tx_err_code(
+        env.c(),
+        tx,
+        "duplicated component",
+        Error::DuplicatedComponent as i8,
+    );
+}
+
+#[test]
+fn test_update_by_owner() {
+    let mut env = Setup::new();
+
+    let id = Bytes::from(vec![1u8; 32]);
+    let old_data = dapp_info("dapp", 1, &["position"], &env.owner_lock_script);
+    let new_data = dapp_info("dapp", 2, &["position", "health"], &env.owner_lock_script);
+    let tx = TransactionBuilder::default()
+        .input(env.input(Some(id.clone()), old_data))
+        .input(env.owner_input())
+        .output(env.output(id))
+        .output_data(new_data.pack())
+        .build();
+    assert_tx_ok(env.c(), tx, "update by owner");
+}
+
+#[test]
+fn test_update_without_owner() {
+    let mut env = Setup::new();
+
+    let id = Bytes::from(vec![1u8; 32]);
+    let old_data = dapp_info("dapp", 1, &["position"], &env.owner_lock_script);
+    let new_data = dapp_info("dapp", 2, &["position", "health"], &env.owner_lock_script);
+    let tx = TransactionBuilder::default()
+        .input(env.input(Some(id.clone()), old_data))
+        .output(env.output(id))
+        .output_data(new_data.pack())
+        .build();
+    assert_tx_err_code(
+        env.c(),
+        tx,
+        "update without owner",
+        Error::Unauthorized as i8,
+    );
+}
+
+#[test]
+fn test_update_without_version_increased() {
+    let mut env = Setup::new();
+
+    let id = Bytes::from(vec![1u8; 32]);
+    let old_data = dapp_info("dapp", 1, &["position"], &env.owner_lock_script);
+    let new_data = dapp_info("dapp", 1, &["position", "health"], &env.owner_lock_script);
+    let tx = TransactionBuilder::default()
+        .input(env.input(Some(id.clone()), old_data))
+        .input(env.owner_input())
+        .output(env.output(id))
+        .output_data(new_data.pack())
+        .build();
+    assert_tx_err_code(
+        env.c(),
+        tx,
+        "update without version increased",
+        Error::VersionNotIncreased as i8,
+    );
+}
+
+#[test]
+fn test_rename() {
+    let mut env = Setup::new();
+
+    let id = Bytes::from(vec![1u8; 32]);
+    let old_data = dapp_info("dapp", 1, &["position"], &env.owner_lock_script);
+    let new_data = dapp_info("renamed", 2, &["position"], &env.owner_lock_script);
+    let tx = TransactionBuilder::default()
+        .input(env.input(Some(id.clone()), old_data))
+        .input(env.owner_input())
+        .output(env.output(id))
+        .output_data(new_data.pack())
+        .build();
+    assert_tx_err_code(env.c(), tx, "rename", Error::NameChanged as i8);
+}
+
+#[test]
+fn test_delete_without_owner() {
+    let mut env = Setup::new();
+
+    let id = Bytes::from(vec![1u8; 32]);
+    let old_data = dapp_info("dapp", 1, &["position"], &env.owner_lock_script);
+    let tx = TransactionBuilder::default()
+        .input(env.input(Some(id), old_data))
+        .output(
+            packed::CellOutput::new_builder()
+                .capacity(2000u64.pack())
+                .lock(env.always_success_script.clone())
+                .build(),
+        )
+        .output_data(Bytes::new().pack())
+        .build();
+    assert_tx_err_code(
+        env.c(),
+        tx,
+        "delete without owner",
+        Error::Unauthorized as i8,
+    );
+}
@@ -0,0 +1,96 @@
+use super::*;
+use ckb_testtool::{
+    ckb_types::{bytes::Bytes, core::TransactionBuilder, packed, prelude::*},
+    context::Context,
+};
+
+include!("../../contracts/entity-type/src/error_include.rs");
+
+pub struct Setup {
+    pub context: Context,
+
+    pub entity_type_out_point: packed::OutPoint,
+    pub always_success_script: packed::Script,
+}
+
+impl Setup {
+    fn new() -> Self {
+        let mut context = Context::default();
+
+        let always_success_out_point =
+            context.deploy_cell(Loader::default().load_binary("always-success"));
+        let entity_type_out_point =
+            context.deploy_cell(Loader::default().load_binary("entity-type"));
+
+        let always_success_script = context
+            .build_script(&always_success_out_point, Bytes::new())
+            .expect("script");
+
+        Self {
+            context,
+
+            entity_type_out_point,
+            always_success_script,
+        }
+    }
+
+    fn c(&mut self) -> &mut Context {
+        return &mut self.context;
+    }
+
+    fn input(&mut self) -> packed::CellInput {
+        let out_point = self.context.create_cell(
+            packed::CellOutput::new_builder()
+                .capacity(2000u64.pack())
+                .lock(self.always_success_script.clone())
+                .build(),
+            Bytes::new(),
+        );
+        packed::CellInput::new_builder()
+            .previous_output(out_point)
+            .build()
+    }
+
+    fn output(&mut self, id: Bytes) -> packed::CellOutput {
+        let script = self
+            .context
+            .build_script(&self.entity_type_out_point, id)
+            .expect("script");
+        packed::CellOutput::new_builder()
+            .capacity(2000u64.pack())
+            .lock(self.always_success_script.clone())
+            .type_(Some(script).pack())
+            .build()
+    }
+}
+
+#[test]
+fn test_create_entity() {
+    let mut env = Setup::new();
+
+    let input = env.input();
+    let new_id = new_type_id(&input, 0);
+    let tx = TransactionBuilder::default()
+        .input(input)
+        .output(env.output(new_id))
+        .output_data(Bytes::new().pack())
+        .build();
+    assert_tx_ok(env.c(), tx, "create entity");
+}
+
+#[test]
+fn test_create_entity_with_invalid_id() {
+    let mut env = Setup::new();
+
+    let tx = TransactionBuilder::default()
+        .input(env.input())
+        .output(env.output(Bytes::from(vec![1u8; 32])))
+        .output_data(Bytes::new().pack())
+        .build();
+    assert_tx_err_code(
+        env.c(),
+        tx,
+        "create entity with invalid id",
+        Error::InvalidTypeID as i8,
+    );
+}
@@ -2,7 +2,7 @@ use blake2b_rs::{Blake2b, Blake2bBuilder};
 use ckb_testtool::{
     ckb_error::Error as CKBError,
     ckb_jsonrpc_types,
-    ckb_types::{bytes::Bytes, core::TransactionView},
+    ckb_types::{bytes::Bytes, core::TransactionView, packed, prelude::*},
     context::Context,
 };
 use std::env;
@@ -18,6 +18,16 @@ mod component_definition_type_tests;
 mod component_lock_tests;
 #[cfg(test)]
 mod component_type_tests;
+#[cfg(test)]
+mod dapp_info_type_tests;
+#[cfg(test)]
+mod entity_type_tests;
+#[cfg(test)]
+mod nonce_type_tests;
+#[cfg(test)]
+mod system_type_tests;
+#[cfg(test)]
+mod typed_message_lock_tests;
 
 const TEST_ENV_VAR: &str = "CAPSULE_TEST_ENV";
 
@@ -95,6 +105,16 @@ pub fn ckb_hash(data: &[u8]) -> Vec<u8> {
     ret
 }
 
+pub fn new_type_id(input: &packed::CellInput, output_index: u64) -> Bytes {
+    let mut blake2b = new_blake2b();
+
+    blake2b.update(input.as_slice());
+    blake2b.update(&output_index.to_le_bytes());
+    let mut ret = vec![0; 32];
+    blake2b.finalize(&mut ret);
+    Bytes::from(ret)
+}
+
 pub fn dump_tx(tx: &TransactionView) {
     let json: ckb_jsonrpc_types::TransactionView = tx.clone().into();
     println!("{}", serde_json::to_string(&json).unwrap());
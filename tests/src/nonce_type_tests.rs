@@ -0,0 +1,174 @@
+use super::*;
+use ckb_testtool::{
+    ckb_types::{bytes::Bytes, core::TransactionBuilder, packed, prelude::*},
+    context::Context,
+};
+
+include!("../../contracts/nonce-type/src/error_include.rs");
+
+pub struct Setup {
+    pub context: Context,
+
+    pub nonce_type_out_point: packed::OutPoint,
+    pub always_success_script: packed::Script,
+}
+
+impl Setup {
+    fn new() -> Self {
+        let mut context = Context::default();
+
+        let always_success_out_point =
+            context.deploy_cell(Loader::default().load_binary("always-success"));
+        let nonce_type_out_point = context.deploy_cell(Loader::default().load_binary("nonce-type"));
+
+        let always_success_script = context
+            .build_script(&always_success_out_point, Bytes::new())
+            .expect("script");
+
+        Self {
+            context,
+
+            nonce_type_out_point,
+            always_success_script,
+        }
+    }
+
+    fn c(&mut self) -> &mut Context {
+        &mut self.context
+    }
+
+    fn input(&mut self) -> packed::CellInput {
+        let out_point = self.context.create_cell(
+            packed::CellOutput::new_builder()
+                .capacity(2000u64.pack())
+                .lock(self.always_success_script.clone())
+                .build(),
+            Bytes::new(),
+        );
+        packed::CellInput::new_builder()
+            .previous_output(out_point)
+            .build()
+    }
+
+    fn nonce_input(&mut self, id: Bytes, nonce: u64) -> packed::CellInput {
+        let output = self.output(id);
+        let out_point = self.context.create_cell(output, nonce_data(nonce));
+        packed::CellInput::new_builder()
+            .previous_output(out_point)
+            .build()
+    }
+
+    fn output(&mut self, id: Bytes) -> packed::CellOutput {
+        let script = self
+            .context
+            .build_script(&self.nonce_type_out_point, id)
+            .expect("script");
+        packed::CellOutput::new_builder()
+            .capacity(2000u64.pack())
+            .lock(self.always_success_script.clone())
+            .type_(Some(script).pack())
+            .build()
+    }
+}
+
+fn nonce_data(nonce: u64) -> Bytes {
+    Bytes::from(nonce.to_le_bytes().to_vec())
+}
+
+#[test]
+fn test_create_nonce() {
+    let mut env = Setup::new();
+
+    let input = env.input();
+    let new_id = new_type_id(&input, 0);
+    let tx = TransactionBuilder::default()
+        .input(input)
+        .output(env.output(new_id))
+        .output_data(nonce_data(0).pack())
+        .build();
+    assert_tx_ok(env.c(), tx, "create nonce");
+}
+
+#[test]
+fn test_create_nonce_not_from_zero() {
+    let mut env = Setup::new();
+
+    let input = env.input();
+    let new_id = new_type_id(&input, 0);
+    let tx = TransactionBuilder::default()
+        .input(input)
+        .output(env.output(new_id))
+        .output_data(nonce_data(1).pack())
+        .build();
+    assert_tx_err_code(
+        env.c(),
+        tx,
+        "create nonce not from zero",
+        Error::InvalidInitialNonce as i8,
+    );
+}
+
+#[test]
+fn test_create_nonce_with_invalid_data() {
+    let mut env = Setup::new();
+
+    let input = env.input();
+    let new_id = new_type_id(&input, 0);
+    let tx = TransactionBuilder::default()
+        .input(input)
+        .output(env.output(new_id))
+        .output_data(Bytes::from(vec![0u8; 4]).pack())
+        .build();
+    assert_tx_err_code(
+        env.c(),
+        tx,
+        "create nonce with invalid data",
+        Error::InvalidData as i8,
+    );
+}
+
+#[test]
+fn test_increase_nonce() {
+    let mut env = Setup::new();
+
+    let id = Bytes::from(vec![1u8; 32]);
+    let tx = TransactionBuilder::default()
+        .input(env.nonce_input(id.clone(), 5))
+        .output(env.output(id))
+        .output_data(nonce_data(6).pack())
+        .build();
+    assert_tx_ok(env.c(), tx, "increase nonce");
+}
+
+#[test]
+fn test_invalid_nonce_increment() {
+    let mut env = Setup::new();
+
+    for (nonce, msg) in [(5, "keep nonce"), (7, "skip nonce"), (4, "decrease nonce")] {
+        let id = Bytes::from(vec![1u8; 32]);
+        let tx = TransactionBuilder::default()
+            .input(env.nonce_input(id.clone(), 5))
+            .output(env.output(id))
+            .output_data(nonce_data(nonce).pack())
+            .build();
+        assert_tx_err_code(env.c(), tx, msg, Error::InvalidIncrement as i8);
+    }
+}
+
+#[test]
+fn test_destroy_nonce() {
+    let mut env = Setup::new();
+
+    let id = Bytes::from(vec![1u8; 32]);
+    let tx = TransactionBuilder::default()
+        .input(env.nonce_input(id, 5))
+        .output(
+            packed::CellOutput::new_builder()
+                .capacity(2000u64.pack())
+                .lock(env.always_success_script.clone())
+                .build(),
+        )
+        .output_data(Bytes::new().pack())
+        .build();
+    assert_tx_ok(env.c(), tx, "destroy nonce");
+}
@@ -0,0 +1,144 @@
+use super::*;
+use ckb_testtool::{
+    ckb_types::{bytes::Bytes, core::TransactionBuilder, packed, prelude::*},
+    context::Context,
+};
+
+include!("../../contracts/system-type/src/error_include.rs");
+
+pub struct Setup {
+    pub context: Context,
+
+    pub system_type_out_point: packed::OutPoint,
+    pub always_success_script: packed::Script,
+}
+
+fn system(name: &str, writes: &[[u8; 32]]) -> Bytes {
+    use ckb_ecs_schemas::*;
+    use molecule::prelude::*;
+
+    let writes = Byte32VecBuilder::default()
+        .extend(writes.iter().map(|id| (*id).into()))
+        .build();
+    SystemDefinitionBuilder::default()
+        .name(name.into())
+        .writes(writes)
+        .build()
+        .as_bytes()
+}
+
+impl Setup {
+    fn new() -> Self {
+        let mut context = Context::default();
+
+        let always_success_out_point =
+            context.deploy_cell(Loader::default().load_binary("always-success"));
+        let system_type_out_point =
+            context.deploy_cell(Loader::default().load_binary("system-type"));
+
+        let always_success_script = context
+            .build_script(&always_success_out_point, Bytes::new())
+            .expect("script");
+
+        Self {
+            context,
+
+            system_type_out_point,
+            always_success_script,
+        }
+    }
+
+    fn c(&mut self) -> &mut Context {
+        return &mut self.context;
+    }
+
+    fn input(&mut self, id_opt: Option<Bytes>, data: Bytes) -> packed::CellInput {
+        let type_opt = id_opt.map(|id| {
+            self.context
+                .build_script(&self.system_type_out_point, id)
+                .expect("script")
+        });
+        let out_point = self.context.create_cell(
+            packed::CellOutput::new_builder()
+                .capacity(2000u64.pack())
+                .lock(self.always_success_script.clone())
+                .type_(type_opt.pack())
+                .build(),
+            data,
+        );
+        packed::CellInput::new_builder()
+            .previous_output(out_point)
+            .build()
+    }
+
+    fn output(&mut self, id: Bytes) -> packed::CellOutput {
+        let script = self
+            .context
+            .build_script(&self.system_type_out_point, id)
+            .expect("script");
+        packed::CellOutput::new_builder()
+            .capacity(2000u64.pack())
+            .lock(self.always_success_script.clone())
+            .type_(Some(script).pack())
+            .build()
+    }
+}
+
+#[test]
+fn test_create_system() {
+    let mut env = Setup::new();
+
+    let input = env.input(None, Bytes::new());
+    let new_id = new_type_id(&input, 0);
+    let tx = TransactionBuilder::default()
+        .input(input)
+        .output(env.output(new_id))
+        .output_data(system("movement", &[[1u8; 32]]).pack())
+        .build();
+    assert_tx_ok(env.c(), tx, "create system");
+}
+
+#[test]
+fn test_invalid_data() {
+    let mut env = Setup::new();
+
+    let input = env.input(None, Bytes::new());
+    let new_id = new_type_id(&input, 0);
+    let tx = TransactionBuilder::default()
+        .input(input)
+        .output(env.output(new_id))
+        .output_data(Bytes::from(vec![0u8; 4]).pack())
+        .build();
+    assert_tx_err_code(
+        env.c(),
+        tx,
+        "create system with invalid data",
+        Error::InvalidData as i8,
+    );
+}
+
+#[test]
+fn test_update_writes() {
+    let mut env = Setup::new();
+
+    let id = Bytes::from(vec![1u8; 32]);
+    let tx = TransactionBuilder::default()
+        .input(env.input(Some(id.clone()), system("movement", &[[1u8; 32]])))
+        .output(env.output(id))
+        .output_data(system("movement", &[[1u8; 32], [2u8; 32]]).pack())
+        .build();
+    assert_tx_ok(env.c(), tx, "update writes");
+}
+
+#[test]
+fn test_rename() {
+    let mut env = Setup::new();
+
+    let id = Bytes::from(vec![1u8; 32]);
+    let tx = TransactionBuilder::default()
+        .input(env.input(Some(id.clone()), system("movement", &[])))
+        .output(env.output(id))
+        .output_data(system("renamed", &[]).pack())
+        .build();
+    assert_tx_err_code(env.c(), tx, "rename", Error::NameChanged as i8);
+}
@@ -0,0 +1,675 @@
+use super::*;
+use ckb_ecs_schemas::{
+    definition_flags, typed_hash::domain_separator, ComponentDefinition, TypedMessage,
+};
+use ckb_testtool::{
+    ckb_types::{
+        bytes::Bytes,
+        core::{TransactionBuilder, TransactionView},
+        packed,
+        prelude::*,
+    },
+    context::Context,
+};
+
+include!("../../contracts/typed-message-lock/src/error_include.rs");
+
+mod verifier_example {
+    include!("../../contracts/verifier-example/src/error_include.rs");
+}
+
+const INFO_HASH: [u8; 32] = [5u8; 32];
+const VERIFIER_ARGS: &[u8] = &[7u8];
+
+pub struct Setup {
+    pub context: Context,
+
+    pub always_success_out_point: packed::OutPoint,
+    pub typed_message_lock_out_point: packed::OutPoint,
+    pub verifier_out_point: packed::OutPoint,
+    pub nonce_type_out_point: packed::OutPoint,
+
+    pub owner_lock_script: packed::Script,
+    pub lock_script: packed::Script,
+    // The verifier is exec'ed, so it must be added to cell deps explicitly.
+    pub verifier_cell_dep: packed::CellDep,
+
+    pub definition_id: [u8; 32],
+    pub definition_cell_dep: packed::CellDep,
+    pub component_type_script: packed::Script,
+}
+
+impl Setup {
+    fn new() -> Self {
+        let mut context = Context::default();
+        let always_success_out_point =
+            context.deploy_cell(Loader::default().load_binary("always-success"));
+        let typed_message_lock_out_point =
+            context.deploy_cell(Loader::default().load_binary("typed-message-lock"));
+        let verifier_out_point =
+            context.deploy_cell(Loader::default().load_binary("verifier-example"));
+        let nonce_type_out_point = context.deploy_cell(Loader::default().load_binary("nonce-type"));
+
+        let owner_lock_script = context
+            .build_script(&always_success_out_point, Bytes::from(vec![42]))
+            .expect("script");
+        let verifier_script = context
+            .build_script(&verifier_out_point, Bytes::from(VERIFIER_ARGS))
+            .expect("script");
+        let nonce_type_script = context
+            .build_script(&nonce_type_out_point, Bytes::from(vec![9u8; 32]))
+            .expect("script");
+        let lock_args = lock_args(
+            &owner_lock_script,
+            &verifier_script,
+            nonce_type_script.calc_script_hash().unpack(),
+        );
+        let lock_script = context
+            .build_script(&typed_message_lock_out_point, lock_args)
+            .expect("script");
+        let verifier_cell_dep = packed::CellDep::new_builder()
+            .out_point(verifier_out_point.clone())
+            .build();
+
+        let mut env = Self {
+            context,
+            always_success_out_point,
+            typed_message_lock_out_point,
+            verifier_out_point,
+            nonce_type_out_point,
+            owner_lock_script,
+            lock_script,
+            verifier_cell_dep,
+            definition_id: [0u8; 32],
+            definition_cell_dep: packed::CellDep::default(),
+            component_type_script: packed::Script::default(),
+        };
+        env.use_definition(definition_flags::MESSAGE_REQUIRED, 0);
+        env
+    }
+
+    // The always-success script stands in for component-type, which the lock only reads the args
+    // from.
+    fn use_definition(&mut self, flags: u32, hash_type: u8) {
+        let definition = create_definition(INFO_HASH, flags);
+        self.definition_id = ckb_hash(definition.as_slice())
+            .try_into()
+            .expect("32 bytes");
+        let definition_out_point = self.context.create_cell(
+            packed::CellOutput::new_builder()
+                .capacity(1000u64.pack())
+                .lock(self.owner_lock_script.clone())
+                .build(),
+            definition.as_bytes(),
+        );
+        self.definition_cell_dep = packed::CellDep::new_builder()
+            .out_point(definition_out_point)
+            .build();
+        let mut component_args = self.definition_id.to_vec();
+        component_args.push(hash_type);
+        self.component_type_script = self
+            .context
+            .build_script(&self.always_success_out_point, Bytes::from(component_args))
+            .expect("script");
+    }
+
+    fn c(&mut self) -> &mut Context {
+        &mut self.context
+    }
+
+    fn owner_input(&mut self) -> packed::CellInput {
+        let out_point = self.context.create_cell(
+            packed::CellOutput::new_builder()
+                .capacity(1000u64.pack())
+                .lock(self.owner_lock_script.clone())
+                .build(),
+            Bytes::new(),
+        );
+        packed::CellInput::new_builder()
+            .previous_output(out_point)
+            .build()
+    }
+
+    fn component_input(&mut self, capacity: u64) -> packed::CellInput {
+        self.locked_component_input(self.lock_script.clone(), capacity)
+    }
+
+    fn component_output(&self, capacity: u64) -> packed::CellOutput {
+        self.locked_component_output(self.lock_script.clone(), capacity)
+    }
+
+    fn locked_component_input(&mut self, lock: packed::Script, capacity: u64) -> packed::CellInput {
+        let output = self.locked_component_output(lock, capacity);
+        let out_point = self.context.create_cell(output, Bytes::new());
+        packed::CellInput::new_builder()
+            .previous_output(out_point)
+            .build()
+    }
+
+    fn locked_component_output(&self, lock: packed::Script, capacity: u64) -> packed::CellOutput {
+        packed::CellOutput::new_builder()
+            .capacity(capacity.pack())
+            .lock(lock)
+            .type_(Some(self.component_type_script.clone()).pack())
+            .build()
+    }
+
+    // Returns the lock script which requires the nonce.
+    fn nonce_lock_script(
+        &mut self,
+        verifier_args: &[u8],
+        nonce_type_script: &packed::Script,
+    ) -> packed::Script {
+        let verifier_script = self
+            .context
+            .build_script(
+                &self.verifier_out_point,
+                Bytes::from(verifier_args.to_vec()),
+            )
+            .expect("script");
+        let lock_args = lock_args(
+            &self.owner_lock_script,
+            &verifier_script,
+            nonce_type_script.calc_script_hash().unpack(),
+        );
+        self.context
+            .build_script(&self.typed_message_lock_out_point, lock_args)
+            .expect("script")
+    }
+
+    fn nonce_type_script(&mut self) -> packed::Script {
+        self.nonce_type_script_with_seed(9)
+    }
+
+    fn nonce_type_script_with_seed(&mut self, seed: u8) -> packed::Script {
+        self.context
+            .build_script(&self.nonce_type_out_point, Bytes::from(vec![seed; 32]))
+            .expect("script")
+    }
+
+    fn nonce_type_hash(&mut self) -> [u8; 32] {
+        self.nonce_type_script().calc_script_hash().unpack()
+    }
+
+    fn nonce_input(&mut self, nonce_type_script: &packed::Script, nonce: u64) -> packed::CellInput {
+        let output = self.nonce_output(nonce_type_script);
+        let out_point = self
+            .context
+            .create_cell(output, Bytes::from(nonce.to_le_bytes().to_vec()));
+        packed::CellInput::new_builder()
+            .previous_output(out_point)
+            .build()
+    }
+
+    fn nonce_output(&self, nonce_type_script: &packed::Script) -> packed::CellOutput {
+        packed::CellOutput::new_builder()
+            .capacity(1000u64.pack())
+            .lock(self.owner_lock_script.clone())
+            .type_(Some(nonce_type_script.clone()).pack())
+            .build()
+    }
+
+    fn target(&self) -> [u8; 32] {
+        self.component_type_script.calc_script_hash().unpack()
+    }
+}
+
+fn lock_args(
+    owner_lock_script: &packed::Script,
+    verifier_script: &packed::Script,
+    nonce: [u8; 32],
+) -> Bytes {
+    use ckb_ecs_schemas::TypedMessageLockArgsBuilder;
+
+    let owner: [u8; 32] = owner_lock_script.calc_script_hash().unpack();
+    TypedMessageLockArgsBuilder::default()
+        .owner(owner.into())
+        .verifier(
+            ckb_ecs_schemas::Script::from_slice(verifier_script.as_slice()).expect("compatible"),
+        )
+        .nonce(nonce.into())
+        .build()
+        .as_bytes()
+}
+
+fn create_definition(info_hash: [u8; 32], flags: u32) -> ComponentDefinition {
+    use ckb_ecs_schemas::{ComponentDefinitionBuilder, ComponentDefinitionV2Builder};
+
+    let definition_v2 = ComponentDefinitionV2Builder::default()
+        .component_name("test".into())
+        .info_hash(info_hash.into())
+        .flags(flags.into())
+        .build();
+    ComponentDefinitionBuilder::default()
+        .set(definition_v2)
+        .build()
+}
+
+fn typed_message(target: [u8; 32]) -> TypedMessage {
+    typed_message_with_nonce(target, 0)
+}
+
+fn typed_message_with_nonce(target: [u8; 32], nonce: u64) -> TypedMessage {
+    use ckb_ecs_schemas::TypedMessageBuilder;
+
+    TypedMessageBuilder::default()
+        .action("move".into())
+        .target(target.into())
+        .payload((&[1u8, 2, 3] as &[u8]).into())
+        .nonce(nonce.into())
+        .build()
+}
+
+// The signature accepted by verifier-example.
+fn sign(
+    message: &TypedMessage,
+    info_hash: [u8; 32],
+    definition_id: [u8; 32],
+    nonce_type_hash: [u8; 32],
+) -> Bytes {
+    sign_with_verifier_args(
+        message,
+        info_hash,
+        definition_id,
+        nonce_type_hash,
+        VERIFIER_ARGS,
+    )
+}
+
+fn sign_with_verifier_args(
+    message: &TypedMessage,
+    info_hash: [u8; 32],
+    definition_id: [u8; 32],
+    nonce_type_hash: [u8; 32],
+    verifier_args: &[u8],
+) -> Bytes {
+    let domain = domain_separator(&info_hash, &definition_id, &nonce_type_hash);
+    let digest = message.as_reader().digest(&domain);
+    let mut data = verifier_args.to_vec();
+    data.extend_from_slice(&digest);
+    Bytes::from(ckb_hash(&data))
+}
+
+fn witness(signature: Option<Bytes>, message: &TypedMessage) -> Bytes {
+    packed::WitnessArgs::new_builder()
+        .lock(signature.pack())
+        .input_type(Some(message.as_bytes()).pack())
+        .build()
+        .as_bytes()
+}
+
+// The message uses nonce 0, which is consumed by the tx.
+fn signed_tx(env: &mut Setup, message: &TypedMessage, signature: Option<Bytes>) -> TransactionView {
+    let nonce_type_script = env.nonce_type_script();
+    TransactionBuilder::default()
+        .input(env.component_input(1000))
+        .input(env.nonce_input(&nonce_type_script, 0))
+        .output(env.component_output(1000))
+        .output_data(Bytes::new().pack())
+        .output(env.nonce_output(&nonce_type_script))
+        .output_data(Bytes::from(1u64.to_le_bytes().to_vec()).pack())
+        .cell_dep(env.definition_cell_dep.clone())
+        .cell_dep(env.verifier_cell_dep.clone())
+        .witness(witness(signature, message).pack())
+        .build()
+}
+
+#[test]
+fn test_owner_unlocking() {
+    let mut env = Setup::new();
+
+    let tx = TransactionBuilder::default()
+        .input(env.owner_input())
+        .input(env.component_input(1000))
+        .output(env.component_output(500))
+        .output_data(Bytes::new().pack())
+        .cell_dep(env.definition_cell_dep.clone())
+        .build();
+    assert_tx_ok(env.c(), tx, "owner unlocking");
+}
+
+#[test]
+fn test_signed_message() {
+    let mut env = Setup::new();
+
+    let message = typed_message(env.target());
+    let signature = sign(
+        &message,
+        INFO_HASH,
+        env.definition_id,
+        env.nonce_type_hash(),
+    );
+    let tx = signed_tx(&mut env, &message, Some(signature));
+    assert_tx_ok(env.c(), tx, "signed message");
+}
+
+#[test]
+fn test_signature_required() {
+    let mut env = Setup::new();
+
+    let message = typed_message(env.target());
+    let tx = signed_tx(&mut env, &message, None);
+    assert_tx_err_code(
+        env.c(),
+        tx,
+        "signature required",
+        Error::SignatureRequired as i8,
+    );
+}
+
+#[test]
+fn test_invalid_signature() {
+    let mut env = Setup::new();
+
+    let message = typed_message(env.target());
+    let tx = signed_tx(&mut env, &message, Some(Bytes::from(vec![0u8; 32])));
+    assert_tx_err_code(
+        env.c(),
+        tx,
+        "invalid signature",
+        verifier_example::Error::InvalidSignature as i8,
+    );
+}
+
+#[test]
+fn test_message_signed_for_another_domain() {
+    let mut env = Setup::new();
+
+    let message = typed_message(env.target());
+    let signature = sign(
+        &message,
+        [6u8; 32],
+        env.definition_id,
+        env.nonce_type_hash(),
+    );
+    let tx = signed_tx(&mut env, &message, Some(signature));
+    assert_tx_err_code(
+        env.c(),
+        tx,
+        "signed for another dapp",
+        verifier_example::Error::InvalidSignature as i8,
+    );
+}
+
+#[test]
+fn test_message_target_mismatch() {
+    let mut env = Setup::new();
+
+    let message = typed_message([0u8; 32]);
+    let signature = sign(
+        &message,
+        INFO_HASH,
+        env.definition_id,
+        env.nonce_type_hash(),
+    );
+    let tx = signed_tx(&mut env, &message, Some(signature));
+    assert_tx_err_code(
+        env.c(),
+        tx,
+        "message target mismatch",
+        Error::MessageTargetMismatch as i8,
+    );
+}
+
+#[test]
+fn test_message_target_mismatch_in_group() {
+    let mut env = Setup::new();
+
+    // The owner's other cell in the same lock group is not the message target.
+    let other_cell = env.context.create_cell(
+        packed::CellOutput::new_builder()
+            .capacity(1000u64.pack())
+            .lock(env.lock_script.clone())
+            .build(),
+        Bytes::new(),
+    );
+    let message = typed_message(env.target());
+    let signature = sign(
+        &message,
+        INFO_HASH,
+        env.definition_id,
+        env.nonce_type_hash(),
+    );
+    let tx = signed_tx(&mut env, &message, Some(signature));
+    let tx = tx
+        .as_advanced_builder()
+        .input(
+            packed::CellInput::new_builder()
+                .previous_output(other_cell)
+                .build(),
+        )
+        .output(
+            packed::CellOutput::new_builder()
+                .capacity(1000u64.pack())
+                .lock(env.lock_script.clone())
+                .build(),
+        )
+        .output_data(Bytes::new().pack())
+        .build();
+    assert_tx_err_code(
+        env.c(),
+        tx,
+        "message target mismatch in group",
+        Error::MessageTargetMismatch as i8,
+    );
+}
+
+#[test]
+fn test_capacity_not_preserved() {
+    let mut env = Setup::new();
+
+    let message = typed_message(env.target());
+    let signature = sign(
+        &message,
+        INFO_HASH,
+        env.definition_id,
+        env.nonce_type_hash(),
+    );
+    let tx = TransactionBuilder::default()
+        .input(env.component_input(1000))
+        .output(env.component_output(999))
+        .output_data(Bytes::new().pack())
+        .cell_dep(env.definition_cell_dep.clone())
+        .cell_dep(env.verifier_cell_dep.clone())
+        .witness(witness(Some(signature), &message).pack())
+        .build();
+    assert_tx_err_code(
+        env.c(),
+        tx,
+        "capacity not preserved",
+        Error::CapacityNotPreserved as i8,
+    );
+}
+
+#[test]
+fn test_message_not_required() {
+    let mut env = Setup::new();
+    env.use_definition(0, 0);
+
+    let message = typed_message(env.target());
+    let signature = sign(
+        &message,
+        INFO_HASH,
+        env.definition_id,
+        env.nonce_type_hash(),
+    );
+    let tx = signed_tx(&mut env, &message, Some(signature));
+    assert_tx_err_code(
+        env.c(),
+        tx,
+        "definition does not require the message",
+        Error::MessageNotRequired as i8,
+    );
+}
+
+#[test]
+fn test_invalid_component_args() {
+    let mut env = Setup::new();
+    // 3 is not a valid hash type
+    env.use_definition(definition_flags::MESSAGE_REQUIRED, 3);
+
+    let message = typed_message(env.target());
+    let signature = sign(
+        &message,
+        INFO_HASH,
+        env.definition_id,
+        env.nonce_type_hash(),
+    );
+    let tx = signed_tx(&mut env, &message, Some(signature));
+    assert_tx_err_code(
+        env.c(),
+        tx,
+        "invalid component args",
+        Error::InvalidComponentArgs as i8,
+    );
+}
+
+// Returns the tx which updates the component locked by the nonce lock, and consumes the nonce.
+fn nonce_tx(env: &mut Setup, input_nonce: u64, message_nonce: u64) -> TransactionView {
+    let nonce_type_script = env.nonce_type_script();
+    let lock_script = env.nonce_lock_script(VERIFIER_ARGS, &nonce_type_script);
+
+    let message = typed_message_with_nonce(env.target(), message_nonce);
+    let signature = sign(
+        &message,
+        INFO_HASH,
+        env.definition_id,
+        env.nonce_type_hash(),
+    );
+    TransactionBuilder::default()
+        .input(env.locked_component_input(lock_script.clone(), 1000))
+        .input(env.nonce_input(&nonce_type_script, input_nonce))
+        .output(env.locked_component_output(lock_script, 1000))
+        .output_data(Bytes::new().pack())
+        .output(env.nonce_output(&nonce_type_script))
+        .output_data(Bytes::from((input_nonce + 1).to_le_bytes().to_vec()).pack())
+        .cell_dep(env.definition_cell_dep.clone())
+        .cell_dep(env.verifier_cell_dep.clone())
+        .witness(witness(Some(signature), &message).pack())
+        .build()
+}
+
+#[test]
+fn test_signed_message_with_nonce() {
+    let mut env = Setup::new();
+
+    let tx = nonce_tx(&mut env, 3, 3);
+    assert_tx_ok(env.c(), tx, "signed message with nonce");
+}
+
+#[test]
+fn test_replay_in_later_transaction() {
+    let mut env = Setup::new();
+
+    // The message was signed for nonce 3, which has been consumed.
+    let tx = nonce_tx(&mut env, 4, 3);
+    assert_tx_err_code(
+        env.c(),
+        tx,
+        "replay in later transaction",
+        Error::NonceMismatch as i8,
+    );
+}
+
+#[test]
+fn test_nonce_required() {
+    let mut env = Setup::new();
+
+    let nonce_type_script = env.nonce_type_script();
+    let lock_script = env.nonce_lock_script(VERIFIER_ARGS, &nonce_type_script);
+    let message = typed_message(env.target());
+    let signature = sign(
+        &message,
+        INFO_HASH,
+        env.definition_id,
+        env.nonce_type_hash(),
+    );
+    let tx = TransactionBuilder::default()
+        .input(env.locked_component_input(lock_script.clone(), 1000))
+        .output(env.locked_component_output(lock_script, 1000))
+        .output_data(Bytes::new().pack())
+        .cell_dep(env.definition_cell_dep.clone())
+        .cell_dep(env.verifier_cell_dep.clone())
+        .witness(witness(Some(signature), &message).pack())
+        .build();
+    assert_tx_err_code(env.c(), tx, "nonce required", Error::NonceRequired as i8);
+}
+
+#[test]
+fn test_replay_in_same_transaction() {
+    let mut env = Setup::new();
+
+    // Two lock groups share the nonce, and both messages use the current nonce.
+    let nonce_type_script = env.nonce_type_script();
+    let first_lock_script = env.nonce_lock_script(VERIFIER_ARGS, &nonce_type_script);
+    let second_verifier_args = [8u8];
+    let second_lock_script = env.nonce_lock_script(&second_verifier_args, &nonce_type_script);
+
+    let message = typed_message(env.target());
+    let nonce_type_hash = nonce_type_script.calc_script_hash().unpack();
+    let first_signature = sign(&message, INFO_HASH, env.definition_id, nonce_type_hash);
+    let second_signature = sign_with_verifier_args(
+        &message,
+        INFO_HASH,
+        env.definition_id,
+        nonce_type_hash,
+        &second_verifier_args,
+    );
+    let tx = TransactionBuilder::default()
+        .input(env.locked_component_input(first_lock_script.clone(), 1000))
+        .input(env.locked_component_input(second_lock_script.clone(), 1000))
+        .input(env.nonce_input(&nonce_type_script, 0))
+        .output(env.locked_component_output(first_lock_script, 1000))
+        .output_data(Bytes::new().pack())
+        .output(env.locked_component_output(second_lock_script, 1000))
+        .output_data(Bytes::new().pack())
+        .output(env.nonce_output(&nonce_type_script))
+        .output_data(Bytes::from(1u64.to_le_bytes().to_vec()).pack())
+        .cell_dep(env.definition_cell_dep.clone())
+        .cell_dep(env.verifier_cell_dep.clone())
+        .witness(witness(Some(first_signature), &message).pack())
+        .witness(witness(Some(second_signature), &message).pack())
+        .build();
+    assert_tx_err_code(
+        env.c(),
+        tx,
+        "replay in same transaction",
+        Error::NonceReused as i8,
+    );
+}
+
+#[test]
+fn test_replay_across_nonce_cells() {
+    let mut env = Setup::new();
+
+    // The locks use the same verifier args but different nonce cells. The message is signed for
+    // the first nonce cell, and replayed on the second one when both are at nonce 0.
+    let first_nonce_type_hash = env.nonce_type_hash();
+    let second_nonce_type_script = env.nonce_type_script_with_seed(10);
+    let second_lock_script = env.nonce_lock_script(VERIFIER_ARGS, &second_nonce_type_script);
+
+    let message = typed_message(env.target());
+    let signature = sign(
+        &message,
+        INFO_HASH,
+        env.definition_id,
+        first_nonce_type_hash,
+    );
+    let tx = TransactionBuilder::default()
+        .input(env.locked_component_input(second_lock_script.clone(), 1000))
+        .input(env.nonce_input(&second_nonce_type_script, 0))
+        .output(env.locked_component_output(second_lock_script, 1000))
+        .output_data(Bytes::new().pack())
+        .output(env.nonce_output(&second_nonce_type_script))
+        .output_data(Bytes::from(1u64.to_le_bytes().to_vec()).pack())
+        .cell_dep(env.definition_cell_dep.clone())
+        .cell_dep(env.verifier_cell_dep.clone())
+        .witness(witness(Some(signature), &message).pack())
+        .build();
+    assert_tx_err_code(
+        env.c(),
+        tx,
+        "replay across nonce cells",
+        verifier_example::Error::InvalidSignature as i8,
+    );
+}